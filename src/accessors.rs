@@ -0,0 +1,141 @@
+use crate::{
+    arguments::{impl_argument, separator, usage_of},
+    command::Command,
+    command_enum::CommandEnum,
+};
+
+use proc_macro2::TokenStream;
+use quote::quote;
+
+/// Generates `command_name`, `prefix`, `prefixed_command`, `description` and
+/// `usage` methods (and `meta`/`required_permission`/`arguments`, if
+/// `meta_type`/`permission_type`/`arguments` is specified), which `match` on
+/// `scrutinee` with one of `patterns` per command.
+pub(crate) fn impl_accessors(
+    receiver: TokenStream,
+    scrutinee: TokenStream,
+    patterns: &[TokenStream],
+    infos: &[Command],
+    global: &CommandEnum,
+) -> TokenStream {
+    let names = infos.iter().map(|c| &c.name);
+    let prefixes = infos.iter().map(|c| &c.prefix);
+    let prefixed = infos.iter().map(|c| c.get_prefixed_command());
+    let descriptions = infos.iter().map(|c| match c.description.as_deref() {
+        Some(d) => quote! { Some(#d) },
+        None => quote! { None },
+    });
+    let usages = infos.iter().map(|c| format!("{}{}", c.prefix, usage_of(c)));
+    let fn_meta = global.meta_type.as_ref().map(|meta_type| {
+        let metas = infos.iter().map(|c| {
+            let names = c.meta.iter().map(|m| &m.name);
+            let values = c.meta.iter().map(|m| &m.value);
+            quote! {
+                #meta_type {
+                    #( #names: #values, )*
+                    ..::core::default::Default::default()
+                }
+            }
+        });
+
+        quote! {
+            /// Returns the user-defined metadata of the command.
+            #[allow(clippy::needless_update)]
+            pub fn meta(#receiver) -> #meta_type {
+                match #scrutinee {
+                    #( #patterns => #metas, )*
+                }
+            }
+        }
+    });
+
+    let fn_required_permission =
+        global.permission_type.as_ref().map(|permission_type| {
+            let permissions = infos.iter().map(|c| match &c.permission {
+                Some(p) => quote! { Some(#p) },
+                None => quote! { None },
+            });
+
+            quote! {
+                /// Returns the permission level required to use the command,
+                /// if any.
+                pub fn required_permission(#receiver) -> Option<#permission_type> {
+                    match #scrutinee {
+                        #( #patterns => #permissions, )*
+                    }
+                }
+            }
+        });
+
+    let fns_arguments = global.arguments.as_ref().map(|arguments| {
+        let values = infos.iter().map(|c| {
+            let values =
+                c.arguments.iter().map(|a| impl_argument(a, arguments));
+            quote! { &[#(#values),*] }
+        });
+        let separators = infos.iter().map(|c| match separator(&c.parser) {
+            Some(s) => quote! { Some(#s) },
+            None => quote! { None },
+        });
+
+        quote! {
+            /// Returns the arguments of the command, i.e. the fields of the
+            /// variant.
+            pub fn arguments(#receiver) -> &'static [#arguments] {
+                match #scrutinee {
+                    #( #patterns => #values, )*
+                }
+            }
+
+            /// Returns the separator of the arguments of the command, if
+            /// they are split.
+            pub fn argument_separator(#receiver) -> Option<&'static str> {
+                match #scrutinee {
+                    #( #patterns => #separators, )*
+                }
+            }
+        }
+    });
+
+    quote! {
+        /// Returns the name of the command, without prefix.
+        pub fn command_name(#receiver) -> &'static str {
+            match #scrutinee {
+                #( #patterns => #names, )*
+            }
+        }
+
+        /// Returns the prefix of the command, e.g. `/`.
+        pub fn prefix(#receiver) -> &'static str {
+            match #scrutinee {
+                #( #patterns => #prefixes, )*
+            }
+        }
+
+        /// Returns the name of the command, with prefix.
+        pub fn prefixed_command(#receiver) -> &'static str {
+            match #scrutinee {
+                #( #patterns => #prefixed, )*
+            }
+        }
+
+        /// Returns the description of the command, if any.
+        pub fn description(#receiver) -> Option<&'static str> {
+            match #scrutinee {
+                #( #patterns => #descriptions, )*
+            }
+        }
+
+        /// Returns the usage of the command: its name with prefix,
+        /// followed by the arguments, e.g. `/ban <user_id> [reason]`.
+        pub fn usage(#receiver) -> &'static str {
+            match #scrutinee {
+                #( #patterns => #usages, )*
+            }
+        }
+
+        #fn_meta
+        #fn_required_permission
+        #fns_arguments
+    }
+}
@@ -0,0 +1,186 @@
+use crate::{
+    command::Command,
+    fields_parse::{option_inner, FieldAttrs, ParserType},
+    Result,
+};
+
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::{Fields, Ident, Type, Visibility};
+
+/// An argument of a command, which is a field of its variant.
+pub(crate) struct Argument {
+    /// Name of the field (or `#[command(rename = "...")]` of it), `argN` for
+    /// unnamed fields.
+    pub name: String,
+    /// Whether the name is given by the field or its `rename`, rather than
+    /// made up (`argN`).
+    pub named: bool,
+    /// Type of the field as written in the source.
+    pub type_name: String,
+    /// Whether the argument can be omitted, i.e. the field is an `Option`.
+    pub optional: bool,
+    /// Whether the argument takes the rest of the text, including
+    /// separators.
+    pub variadic: bool,
+}
+
+impl Argument {
+    /// Returns arguments of a variant with `fields`, parsed by `parser`.
+    pub fn from_fields(
+        fields: &Fields,
+        parser: &ParserType,
+    ) -> Result<Vec<Self>> {
+        fields
+            .iter()
+            .enumerate()
+            .map(|(i, field)| {
+                let attrs = FieldAttrs::from_attributes(&field.attrs)?;
+                let named = attrs.rename.is_some() || field.ident.is_some();
+                let name = match (attrs.rename, &field.ident) {
+                    (Some(rename), _) => rename,
+                    (None, Some(ident)) => ident.to_string(),
+                    (None, None) => format!("arg{i}"),
+                };
+
+                Ok(Self {
+                    name,
+                    named,
+                    type_name: type_name(&field.ty),
+                    optional: option_inner(&field.ty).is_some(),
+                    // The default parser passes all of the text to the field
+                    variadic: matches!(parser, ParserType::Default)
+                        && !attrs.from_mention,
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the argument as it's shown in usage, e.g. `<id>`, `[reason]`
+    /// or `<text...>`.
+    fn usage(&self) -> String {
+        let dots = if self.variadic { "..." } else { "" };
+        match self.optional {
+            true => format!("[{}{dots}]", self.name),
+            false => format!("<{}{dots}>", self.name),
+        }
+    }
+}
+
+/// Returns the separator of arguments of a command parsed by `parser`, if
+/// there are separators.
+pub(crate) fn separator(parser: &ParserType) -> Option<&str> {
+    match parser {
+        ParserType::Split { separator, .. } => {
+            Some(separator.as_deref().unwrap_or(" "))
+        }
+        _ => None,
+    }
+}
+
+/// Returns the command name followed by its arguments, e.g.
+/// `ban <user_id> [reason]`.
+///
+/// Commands with a `pattern` are shown as the pattern itself, since their
+/// fields are a part of the name.
+pub(crate) fn usage_of(command: &Command) -> String {
+    match arguments_usage(command) {
+        Some(arguments) => format!("{} {arguments}", command.name),
+        None => command.name.clone(),
+    }
+}
+
+/// Returns the command as it's shown in the help text: with its arguments
+/// (see `usage_of`) if all of them are named (by the fields or `rename`), and
+/// just the name otherwise, since made up names (`argN`) don't tell anything.
+///
+/// Note that `CommandDescriptions::username` appends `@username` to this.
+pub(crate) fn help_usage_of(command: &Command) -> String {
+    match command.arguments.iter().all(|a| a.named) {
+        true => usage_of(command),
+        false => command.name.clone(),
+    }
+}
+
+/// Returns the arguments of a command joined by its separator, if there are
+/// any outside of the name.
+fn arguments_usage(command: &Command) -> Option<String> {
+    if command.pattern.is_some() || command.arguments.is_empty() {
+        return None;
+    }
+
+    // Whitespace separators are shown as a single space, for readability
+    let separator = match separator(&command.parser) {
+        Some(separator) if !separator.trim().is_empty() => separator,
+        _ => " ",
+    };
+    let arguments = command
+        .arguments
+        .iter()
+        .map(Argument::usage)
+        .collect::<Vec<_>>()
+        .join(separator);
+
+    Some(arguments)
+}
+
+/// Generates a struct `arguments` describing arguments of commands, returned
+/// by the `arguments` accessor.
+pub(crate) fn impl_arguments_type(
+    type_name: &Ident,
+    vis: &Visibility,
+    arguments: &Ident,
+) -> TokenStream {
+    let arguments_doc =
+        format!("An argument of a [`{type_name}`] command, see `arguments`.");
+
+    quote! {
+        #[doc = #arguments_doc]
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        #vis struct #arguments {
+            /// Name of the argument, i.e. of the field.
+            pub name: &'static str,
+            /// Type of the field, as written in the source.
+            pub type_name: &'static str,
+            /// Whether the argument can be omitted.
+            pub optional: bool,
+            /// Whether the argument takes the rest of the text, including
+            /// separators.
+            pub variadic: bool,
+        }
+    }
+}
+
+/// Returns code which constructs the generated `arguments` struct.
+pub(crate) fn impl_argument(
+    argument: &Argument,
+    arguments: &Ident,
+) -> TokenStream {
+    let Argument { name, type_name, optional, variadic, .. } = argument;
+    quote! {
+        #arguments {
+            name: #name,
+            type_name: #type_name,
+            optional: #optional,
+            variadic: #variadic,
+        }
+    }
+}
+
+/// Returns `ty` as it's written in the source, without spaces which
+/// `to_string` puts between tokens.
+fn type_name(ty: &Type) -> String {
+    let mut name = ty.to_token_stream().to_string();
+    for (spaced, tight) in [
+        (" <", "<"),
+        ("< ", "<"),
+        (" >", ">"),
+        (" ,", ","),
+        (" :: ", "::"),
+        ("& ", "&"),
+    ] {
+        name = name.replace(spaced, tight);
+    }
+
+    name
+}
@@ -1,10 +1,14 @@
 use crate::{error::compile_error_at, Result};
 
-use proc_macro2::Span;
+use proc_macro2::{Span, TokenStream};
 use syn::{
+    bracketed, parenthesized,
     parse::{Parse, ParseBuffer, ParseStream},
+    punctuated::Punctuated,
     spanned::Spanned,
-    Attribute, Ident, Lit, Path, Token,
+    token::{Bracket, Paren},
+    Attribute, Expr, ExprLit, ExprPath, Ident, Lit, Path, Token, Type,
+    TypePath,
 };
 
 pub(crate) fn fold_attrs<A, R>(
@@ -36,8 +40,8 @@ pub(crate) fn fold_attrs<A, R>(
 ///
 /// For example:
 /// ```text
-///   #[blahblah(key = "puff", value = 12, nope)]
-///              ^^^^^^^^^^^^  ^^^^^^^^^^  ^^^^
+///   #[blahblah(key = "puff", value = 12, nope, list(a = 1))]
+///              ^^^^^^^^^^^^  ^^^^^^^^^^  ^^^^  ^^^^^^^^^^^
 /// ```
 pub(crate) struct Attr {
     pub key: Ident,
@@ -48,12 +52,25 @@ pub(crate) struct Attr {
 ///
 /// For example:
 /// ```text
-///   #[blahblah(key = "puff", value = 12, nope)]
+///   #[blahblah(key = "puff", value = 12, nope, list(a = 1), f = call(1))]
 ///                    ^^^^^^          ^^     ^-- (None pseudo-value)
+///                                                   ^^^^^--- (Args)
+///                                                               ^^^^^^^
+///                                                               (Call)
 /// ```
 pub(crate) enum AttrValue {
     Path(Path),
     Lit(Lit),
+    /// A path with tokens inside of parentheses after it, which are parsed by
+    /// the attribute itself.
+    Call(Path, TokenStream, Span),
+    /// A list of values in brackets, e.g. `["/", "!"]`.
+    List(Vec<AttrValue>, Span),
+    /// A type which is not a path, e.g. a tuple or a reference.
+    Type(Box<Type>),
+    /// Tokens inside of parentheses, which are parsed by the attribute
+    /// itself.
+    Args(TokenStream, Span),
     None(Span),
 }
 
@@ -61,12 +78,15 @@ impl Parse for Attr {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let key = input.parse::<Ident>()?;
 
-        let value = match input.peek(Token![=]) {
-            true => {
-                input.parse::<Token![=]>()?;
-                input.parse::<AttrValue>()?
-            }
-            false => AttrValue::None(input.span()),
+        let value = if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            input.parse::<AttrValue>()?
+        } else if input.peek(Paren) {
+            let args;
+            let paren = parenthesized!(args in input);
+            AttrValue::Args(args.parse()?, paren.span)
+        } else {
+            AttrValue::None(input.span())
         };
 
         Ok(Self { key, value })
@@ -91,13 +111,83 @@ impl AttrValue {
         })
     }
 
-    // /// Unwraps this value if it's a path.
-    // pub fn expect_path(self) -> Result<Path> {
-    //     self.expect("a path", |this| match this {
-    //         AttrValue::Path(p) => Ok(p),
-    //         _ => Err(this),
-    //     })
-    // }
+    /// Unwraps this value if it's a string literal or a non-empty list of
+    /// them.
+    pub fn expect_strings(self) -> Result<Vec<String>> {
+        match self {
+            AttrValue::List(values, sp) if values.is_empty() => {
+                Err(compile_error_at("expected a non-empty list", sp))
+            }
+            AttrValue::List(values, _) => {
+                values.into_iter().map(AttrValue::expect_string).collect()
+            }
+            this => this.expect_string().map(|s| vec![s]),
+        }
+    }
+
+    /// Unwraps this value if it's a path consisting of a single identifier.
+    pub fn expect_ident(self) -> Result<Ident> {
+        self.expect("an identifier", |this| match this {
+            AttrValue::Path(ref p) => p.get_ident().cloned().ok_or(this),
+            _ => Err(this),
+        })
+    }
+
+    /// Unwraps this value if it's a path.
+    pub fn expect_path(self) -> Result<Path> {
+        self.expect("a path", |this| match this {
+            AttrValue::Path(p) => Ok(p),
+            _ => Err(this),
+        })
+    }
+
+    /// Unwraps this value as a type if it's a path or a type.
+    pub fn expect_type(self) -> Result<Type> {
+        self.expect("a type", |this| match this {
+            AttrValue::Path(path) => {
+                Ok(Type::Path(TypePath { qself: None, path }))
+            }
+            AttrValue::Type(ty) => Ok(*ty),
+            _ => Err(this),
+        })
+    }
+
+    /// Checks that there is no value, i.e. that the attribute is a flag.
+    pub fn expect_none(self) -> Result<()> {
+        self.expect("nothing", |this| match this {
+            AttrValue::None(_) => Ok(()),
+            _ => Err(this),
+        })
+    }
+
+    /// Unwraps this value as an expression if it's a path or a literal.
+    pub fn expect_expr(self) -> Result<Expr> {
+        self.expect("a path or a literal", |this| match this {
+            AttrValue::Path(path) => Ok(Expr::Path(ExprPath {
+                attrs: Vec::new(),
+                qself: None,
+                path,
+            })),
+            AttrValue::Lit(lit) => {
+                Ok(Expr::Lit(ExprLit { attrs: Vec::new(), lit }))
+            }
+            _ => Err(this),
+        })
+    }
+
+    /// Unwraps this value if it's a parenthesized list of arguments and
+    /// parses them with `parser`.
+    pub fn expect_args<T>(
+        self,
+        parser: impl syn::parse::Parser<Output = T>,
+    ) -> Result<T> {
+        let args = self.expect("a parenthesized list", |this| match this {
+            AttrValue::Args(args, _) => Ok(args),
+            _ => Err(this),
+        })?;
+
+        Ok(parser.parse2(args)?)
+    }
 
     pub fn expect<T>(
         self,
@@ -126,19 +216,27 @@ impl AttrValue {
                 Verbatim(_) => ":shrug:",
             },
             Self::Path(_) => "a path",
+            Self::Args(..) => "a parenthesized list",
+            Self::Call(..) => "a function-like call",
+            Self::List(..) => "a list",
+            Self::Type(_) => "a type",
         }
     }
 
     /// Returns span of the value
     ///
     /// ```text
-    ///   #[blahblah(key = "puff", value = 12, nope )]
-    ///                    ^^^^^^          ^^      ^
+    ///   #[blahblah(key = "puff", value = 12, nope , list(a = 1))]
+    ///                    ^^^^^^          ^^      ^      ^^^^^^^
     /// ```
     fn span(&self) -> Span {
         match self {
             Self::Path(p) => p.span(),
             Self::Lit(l) => l.span(),
+            Self::Args(_, sp) => *sp,
+            Self::Call(p, _, sp) => p.span().join(*sp).unwrap_or(*sp),
+            Self::List(_, sp) => *sp,
+            Self::Type(ty) => ty.span(),
             Self::None(sp) => *sp,
         }
     }
@@ -146,9 +244,31 @@ impl AttrValue {
 
 impl Parse for AttrValue {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        let this = match input.peek(Lit) {
-            true => Self::Lit(input.parse()?),
-            false => Self::Path(input.parse()?),
+        if input.peek(Lit) {
+            return Ok(Self::Lit(input.parse()?));
+        }
+
+        if input.peek(Bracket) {
+            let values;
+            let bracket = bracketed!(values in input);
+            let values =
+                Punctuated::<Self, Token![,]>::parse_terminated(&values)?;
+            return Ok(Self::List(values.into_iter().collect(), bracket.span));
+        }
+
+        // Types which don't start with a path, e.g. `(Bot, Message)`
+        if input.peek(Paren) || input.peek(Token![&]) {
+            return Ok(Self::Type(Box::new(input.parse()?)));
+        }
+
+        let path = input.parse()?;
+        let this = match input.peek(Paren) {
+            true => {
+                let args;
+                let paren = parenthesized!(args in input);
+                Self::Call(path, args.parse()?, paren.span)
+            }
+            false => Self::Path(path),
         };
 
         Ok(this)
@@ -1,10 +1,27 @@
 use crate::{
-    command::Command, command_enum::CommandEnum, compile_error,
-    fields_parse::impl_parse_args, unzip::Unzip, Result,
+    accessors::impl_accessors,
+    arguments::impl_arguments_type,
+    categories::{
+        help_order, impl_all_descriptions, impl_visible_descriptions,
+    },
+    command::Command,
+    command_enum::{CaseFolding, CommandEnum, MentionMode, Unprefixed},
+    command_kind::impl_kind,
+    compile_error,
+    deep_link::impl_deep_link,
+    dispatch::impl_dispatch,
+    error::compile_error_at,
+    fields_parse::impl_parse_args,
+    parse_options::impl_parse_options,
+    pattern::{impl_format_pattern, impl_match_pattern},
+    suggestions::impl_suggestions,
+    trigger::impl_parse_trigger,
+    unzip::Unzip,
+    Result,
 };
 
 use proc_macro2::TokenStream;
-use quote::quote;
+use quote::{quote, quote_spanned};
 use syn::DeriveInput;
 
 pub(crate) fn bot_commands_impl(input: DeriveInput) -> Result<TokenStream> {
@@ -18,23 +35,99 @@ pub(crate) fn bot_commands_impl(input: DeriveInput) -> Result<TokenStream> {
             let command = Command::new(
                 &variant.ident.to_string(),
                 &variant.attrs,
+                &variant.fields,
                 &command_enum,
             )?;
 
             let variant_name = &variant.ident;
             let self_variant = quote! { Self::#variant_name };
 
-            let parse =
-                impl_parse_args(&variant.fields, self_variant, &command.parser);
+            let parse = match &command.pattern {
+                // Skipped variants are never parsed, so their fields don't
+                // have to implement `FromStr`
+                _ if command.skip => quote! {},
+                Some(pattern) => {
+                    pattern.impl_match(command.impl_prefix_check(), variant)?
+                }
+                None => impl_parse_args(
+                    &variant.fields,
+                    self_variant,
+                    &command.parser,
+                )?,
+            };
 
             Ok((parse, command))
         })
         .collect::<Result<Unzip<Vec<_>, Vec<_>>>>()?;
 
+    check_duplicates(data_enum.variants.iter(), &var_info, &command_enum)?;
+
+    if command_enum.check_menu {
+        check_menu_rules(data_enum.variants.iter(), &var_info)?;
+    }
+
     let type_name = &input.ident;
+    let variants =
+        data_enum.variants.iter().map(|v| &v.ident).collect::<Vec<_>>();
     let fn_descriptions = impl_descriptions(&var_info, &command_enum);
-    let fn_parse = impl_parse(&var_info, &var_init);
+    let fn_parse = impl_parse(&command_enum);
+    let fn_parse_addressed = impl_parse_addressed(&command_enum);
+    let fn_parse_message = impl_parse_message(&command_enum);
+    let fn_parse_all = impl_parse_all(&var_info, &command_enum);
+    let fn_parse_parts = impl_parse_parts(&var_info, &var_init, &command_enum);
+    let fn_parse_trigger =
+        impl_parse_trigger(&var_info, &var_init, &command_enum);
     let fn_commands = impl_commands(&var_info);
+    let fn_suggestions = impl_suggestions(&var_info);
+    let fn_format_pattern =
+        impl_format_pattern(data_enum.variants.iter(), &var_info)?;
+    let patterns =
+        variants.iter().map(|v| quote! { Self::#v { .. } }).collect::<Vec<_>>();
+    let accessors = impl_accessors(
+        quote! { &self },
+        quote! { *self },
+        &patterns,
+        &var_info,
+        &command_enum,
+    );
+    let fns_permission =
+        command_enum.permission_type.as_ref().map(|permission_type| {
+            impl_permission_filters(&var_info, &command_enum, permission_type)
+        });
+    let fn_deep_link =
+        impl_deep_link(type_name, data_enum.variants.iter(), &var_info)?;
+    let fn_dispatch = command_enum
+        .handler_context
+        .as_ref()
+        .map(|context| {
+            impl_dispatch(
+                data_enum.variants.iter(),
+                &var_info,
+                &command_enum,
+                context,
+            )
+        })
+        .transpose()?;
+    let parse_options = command_enum.parse_options.as_ref().map(|options| {
+        impl_parse_options(type_name, &input.vis, options, &command_enum)
+    });
+    let kind = command_enum.kind.as_ref().map(|kind| {
+        impl_kind(
+            type_name,
+            &input.vis,
+            kind,
+            &variants,
+            &var_info,
+            &command_enum,
+        )
+    });
+
+    let fn_parse_detailed = impl_parse_detailed(&variants, &var_info);
+    let arguments = command_enum
+        .arguments
+        .as_ref()
+        .map(|arguments| impl_arguments_type(type_name, &input.vis, arguments));
+    let deprecations = impl_deprecations(&var_info);
 
     let trait_impl = quote! {
         impl teloxide::utils::command::BotCommands for #type_name {
@@ -42,20 +135,101 @@ pub(crate) fn bot_commands_impl(input: DeriveInput) -> Result<TokenStream> {
             #fn_parse
             #fn_commands
         }
+
+        impl #type_name {
+            #fn_parse_addressed
+            #fn_parse_message
+            #fn_parse_all
+            #fn_parse_parts
+            #fn_parse_trigger
+            #accessors
+            #fns_permission
+            #fn_dispatch
+            #fn_deep_link
+            #fn_format_pattern
+            #fn_parse_detailed
+            #fn_suggestions
+        }
+
+        #kind
+        #parse_options
+        #arguments
+        #deprecations
     };
 
     Ok(trait_impl)
 }
 
-fn impl_commands(infos: &[Command]) -> proc_macro2::TokenStream {
-    let commands = infos
+/// Generates `parse_detailed`, which also returns the hint of a deprecated
+/// command (always `None` if none of the commands are deprecated).
+fn impl_parse_detailed(
+    variants: &[&syn::Ident],
+    infos: &[Command],
+) -> proc_macro2::TokenStream {
+    let arms = variants
         .iter()
-        .filter(|command| command.description_is_enabled())
-        .map(|command| {
-            let c = command.get_prefixed_command();
-            let d = command.description.as_deref().unwrap_or_default();
-            quote! { BotCommand::new(#c,#d) }
-        });
+        .zip(infos)
+        .filter_map(|(variant, command)| {
+            let hint = command.deprecated.as_ref()?;
+            Some(quote! { Self::#variant { .. } => Some(#hint), })
+        })
+        .collect::<Vec<_>>();
+
+    let other = match arms.len() == infos.len() {
+        true => quote! {},
+        false => quote! { _ => None, },
+    };
+    let deprecated = match arms.is_empty() {
+        true => quote! { None },
+        false => quote! {
+            match command {
+                #(#arms)*
+                #other
+            }
+        },
+    };
+
+    quote! {
+        /// Parses a command like `parse`, also returning the hint of the
+        /// command if it's deprecated (`#[command(deprecated = "...")]`), to
+        /// nudge the user towards its replacement.
+        pub fn parse_detailed(s: &str, bot_name: &str) -> Result<(Self, Option<&'static str>), teloxide::utils::command::ParseError> {
+            let command = <Self as teloxide::utils::command::BotCommands>::parse(s, bot_name)?;
+            let deprecated = #deprecated;
+
+            Ok((command, deprecated))
+        }
+    }
+}
+
+/// Generates uses of deprecated items, which make the compiler warn about
+/// deprecated attributes.
+fn impl_deprecations(infos: &[Command]) -> proc_macro2::TokenStream {
+    let warnings = infos.iter().filter_map(|c| c.description_off).map(|sp| {
+        quote_spanned! { sp =>
+            const _: () = {
+                #[deprecated(
+                    note = "`description = \"off\"` is deprecated, use \
+                            `#[command(hide)]` instead"
+                )]
+                const DESCRIPTION_OFF: () = ();
+                DESCRIPTION_OFF
+            };
+        }
+    });
+
+    quote! { #(#warnings)* }
+}
+
+fn impl_commands(infos: &[Command]) -> proc_macro2::TokenStream {
+    let commands =
+        help_order(infos.iter().filter(|command| command.is_in_menu()))
+            .into_iter()
+            .map(|command| {
+                let c = command.get_prefixed_command();
+                let d = command.description.as_deref().unwrap_or_default();
+                quote! { BotCommand::new(#c,#d) }
+            });
 
     quote! {
         fn bot_commands() -> Vec<teloxide::types::BotCommand> {
@@ -69,14 +243,7 @@ fn impl_descriptions(
     infos: &[Command],
     global: &CommandEnum,
 ) -> proc_macro2::TokenStream {
-    let command_descriptions = infos
-        .iter()
-        .filter(|command| command.description_is_enabled())
-        .map(|Command { prefix, name, description, ..}| {
-            let description = description.clone().unwrap_or_default();
-            quote! { CommandDescription { prefix: #prefix, command: #name, description: #description } }
-        });
-
+    let descriptions = impl_all_descriptions(infos);
     let global_description = match global.description.as_deref() {
         Some(gd) => quote! { .global_description(#gd) },
         None => quote! {},
@@ -85,54 +252,735 @@ fn impl_descriptions(
     quote! {
         fn descriptions() -> teloxide::utils::command::CommandDescriptions<'static> {
             use teloxide::utils::command::{CommandDescriptions, CommandDescription};
-            use std::borrow::Cow;
 
-            CommandDescriptions::new(&[
-                #(#command_descriptions),*
-            ])
+            CommandDescriptions::new(#descriptions)
             #global_description
         }
     }
 }
 
-fn impl_parse(
+/// Generates `descriptions_for` and `bot_commands_for`, which only list
+/// commands available at the given permission level.
+fn impl_permission_filters(
     infos: &[Command],
-    variants_initialization: &[proc_macro2::TokenStream],
+    global: &CommandEnum,
+    permission_type: &syn::Path,
 ) -> proc_macro2::TokenStream {
-    let matching_values = infos.iter().map(|c| c.get_prefixed_command());
+    let is_visible = |command: &&Command| match &command.permission {
+        Some(p) => quote! { #p <= level },
+        None => quote! { true },
+    };
+    let fn_visible_descriptions = impl_visible_descriptions(infos);
+    let menu = help_order(infos.iter().filter(|command| command.is_in_menu()));
+    let infos = help_order(infos.iter().filter(|command| command.is_in_help()));
+    let visible = infos.iter().map(is_visible);
+    let menu_visible = menu.iter().map(is_visible);
+    let commands = menu.iter().map(|command| {
+        let c = command.get_prefixed_command();
+        let d = command.description.as_deref().unwrap_or_default();
+        quote! { BotCommand::new(#c,#d) }
+    });
+
+    let global_description = match global.description.as_deref() {
+        Some(gd) => quote! { .global_description(#gd) },
+        None => quote! {},
+    };
+
+    quote! {
+        #fn_visible_descriptions
+
+        /// Returns descriptions of the commands which are available at the
+        /// permission `level`.
+        pub fn descriptions_for(level: #permission_type) -> teloxide::utils::command::CommandDescriptions<'static> {
+            let visible = vec![#(#visible),*];
+            teloxide::utils::command::CommandDescriptions::new(Self::__visible_descriptions(visible))
+            #global_description
+        }
+
+        /// Returns the commands which are available at the permission
+        /// `level`, for use with [`SetMyCommands`].
+        ///
+        /// [`SetMyCommands`]: teloxide::payloads::SetMyCommands
+        pub fn bot_commands_for(level: #permission_type) -> Vec<teloxide::types::BotCommand> {
+            use teloxide::types::BotCommand;
+
+            [#(#commands),*]
+                .into_iter()
+                .zip([#(#menu_visible),*])
+                .filter_map(|(c, v)| v.then_some(c))
+                .collect()
+        }
+    }
+}
+
+fn impl_parse(global: &CommandEnum) -> proc_macro2::TokenStream {
+    let case_insensitive = global.case_insensitive.is_some();
 
     quote! {
          fn parse(s: &str, bot_name: &str) -> Result<Self, teloxide::utils::command::ParseError> {
+              Self::parse_addressed(s, bot_name)?
+                  .ok_or_else(|| Self::__not_addressed(s, None, #case_insensitive))
+         }
+    }
+}
+
+/// Documents what happens to commands which are not addressed to the bot
+/// `bot_name` in the mention mode of the enum, whose `outcome` is given for
+/// the modes where they are not an error.
+fn not_addressed_doc(mention: MentionMode, outcome: &str) -> String {
+    match mention {
+        MentionMode::Optional => "Commands mentioning another bot (e.g. \
+                                  `/command@other_bot`) result in \
+                                  `Err(ParseError::WrongBotName)`."
+            .to_owned(),
+        MentionMode::Required => format!(
+            "Commands which don't mention the bot `bot_name` (e.g. `/command` \
+             or `/command@other_bot`) {outcome}."
+        ),
+        MentionMode::IgnoreOthers => {
+            format!(
+                "Commands mentioning another bot (e.g. `/command@other_bot`) \
+                 {outcome}."
+            )
+        }
+    }
+}
+
+fn impl_parse_addressed(global: &CommandEnum) -> proc_macro2::TokenStream {
+    let case_insensitive = global.case_insensitive.is_some();
+    let not_addressed =
+        not_addressed_doc(global.mention, "result in `Ok(None)`");
+
+    quote! {
+         /// Parses a command, returning `Ok(None)` if it is not addressed to
+         /// the bot `bot_name`.
+         ///
+         #[doc = #not_addressed]
+         ///
+         /// `parse` returns an error for such commands instead: `WrongBotName`
+         /// if another bot is mentioned and, if the mention is required but
+         /// missing, `UnknownCommand` for unknown commands and `Custom` for
+         /// known ones.
+         pub fn parse_addressed(s: &str, bot_name: &str) -> Result<Option<Self>, teloxide::utils::command::ParseError> {
+              Self::__parse_text(s, &[bot_name], None, #case_insensitive)
+         }
+
+         /// Returns the error of a command `s`, which is not addressed to
+         /// the bot.
+         ///
+         /// A command mentioning another bot is `WrongBotName`. A command
+         /// without a mention (which is required then) is `UnknownCommand`
+         /// if there is no such command, and `Custom` otherwise.
+         fn __not_addressed(
+             s: &str,
+             prefixes: Option<&[&str]>,
+             case_insensitive: bool,
+         ) -> teloxide::utils::command::ParseError {
+              use teloxide::utils::command::ParseError;
+
+              // Unwrap: split iterators always have at least one item
+              let full_command = s.split(char::is_whitespace).next().unwrap();
+              if let Some((_, bot_username)) = full_command.split_once('@') {
+                  return ParseError::WrongBotName(bot_username.to_owned());
+              }
+
+              // The command is looked up as if it mentioned the bot, to tell
+              // unknown commands apart
+              let mentioned = format!("{full_command}@");
+              match Self::__parse_parts(&mentioned, "", &[""], prefixes, case_insensitive, &[]) {
+                  Err(ParseError::UnknownCommand(command)) => ParseError::UnknownCommand(command),
+                  _ => ParseError::Custom(
+                      format!("Command {full_command} must mention the bot").into(),
+                  ),
+              }
+         }
+
+         /// Parses a command from text, see `__parse_parts`.
+         fn __parse_text(
+             s: &str,
+             bot_names: &[&str],
+             prefixes: Option<&[&str]>,
+             case_insensitive: bool,
+         ) -> Result<Option<Self>, teloxide::utils::command::ParseError> {
+              if let Some(command) = Self::__parse_trigger(s)? {
+                  return Ok(Some(command));
+              }
+
+              // The command is separated from the arguments by any whitespace
+              // (clients often put a newline right after the command), which
+              // is not a part of the arguments.
+              let (full_command, args) = match s.find(char::is_whitespace) {
+                  Some(i) => (&s[..i], s[i..].trim_start()),
+                  None => (s, ""),
+              };
+
+              let mentions = Self::__text_mentions(args);
+
+              Self::__parse_parts(full_command, args, bot_names, prefixes, case_insensitive, &mentions)
+         }
+
+         /// Returns byte ranges of mentions in `args`, which are `@` followed
+         /// by a username (ASCII letters, digits and `_`), since there are no
+         /// entities. An `@` inside a word (e.g. an email) is not a mention.
+         fn __text_mentions(args: &str) -> Vec<(std::ops::Range<usize>, Option<&'static teloxide::types::User>)> {
+              let is_username = |c: char| c.is_ascii_alphanumeric() || c == '_';
+              let mut mentions = Vec::new();
+              let mut rest = 0;
+              while let Some(at) = args[rest..].find('@') {
+                  let start = rest + at;
+                  let len = args[start + 1..]
+                      .find(|c: char| !is_username(c))
+                      .unwrap_or(args.len() - start - 1);
+                  let in_word = matches!(args[..start].chars().next_back(), Some(c) if is_username(c));
+                  if len > 0 && !in_word {
+                      mentions.push((start..start + 1 + len, None));
+                  }
+                  rest = start + 1 + len;
+              }
+              mentions
+         }
+    }
+}
+
+fn impl_parse_message(global: &CommandEnum) -> proc_macro2::TokenStream {
+    let case_insensitive = global.case_insensitive.is_some();
+    let not_addressed =
+        not_addressed_doc(global.mention, "result in `Ok(None)`");
+    let not_addressed_all = not_addressed_doc(global.mention, "are skipped");
+
+    let unprefixed = match global.unprefixed {
+        Unprefixed::Skip => quote! {},
+        Unprefixed::Report => quote! {
+            use teloxide::utils::command::ParseError;
+
+            let first_command = commands
+                .first()
+                .map_or(text.len(), |c| Self::__byte_offset(text, c.offset));
+            let leading = text[..first_command].trim();
+            if !leading.is_empty() {
+                // Unwrap: split iterators always have at least one item
+                let word = leading.split(char::is_whitespace).next().unwrap();
+                res.push(Err(ParseError::UnknownCommand(word.to_owned())));
+            }
+        },
+    };
+
+    quote! {
+         /// Parses a command from a text or a caption of `message`, using
+         /// [`BotCommand`] entities to locate it.
+         ///
+         /// Returns `Ok(None)` if the message doesn't start with a command
+         /// (note that Telegram only marks `/`-commands) or a trigger keyword.
+         #[doc = #not_addressed]
+         ///
+         /// [`BotCommand`]: teloxide::types::MessageEntityKind::BotCommand
+         pub fn parse_message(message: &teloxide::types::Message, bot_name: &str) -> Result<Option<Self>, teloxide::utils::command::ParseError> {
+              use teloxide::types::MessageEntityKind;
+
+              let (text, entities) = match Self::__text_and_entities(message) {
+                  Some(text_and_entities) => text_and_entities,
+                  None => return Ok(None),
+              };
+
+              let command = entities.iter().find(|e| {
+                  e.offset == 0 && matches!(e.kind, MessageEntityKind::BotCommand)
+              });
+              match command {
+                  Some(command) => Self::__parse_entity(text, entities, command, text.len(), bot_name),
+                  None => Self::__parse_trigger(text),
+              }
+         }
+
+         /// Parses all commands from a text or a caption of `message`, each
+         /// of which starts at a [`BotCommand`] entity and lasts until the
+         /// next one.
+         ///
+         #[doc = #not_addressed_all]
+         ///
+         /// [`BotCommand`]: teloxide::types::MessageEntityKind::BotCommand
+         pub fn parse_all_message(message: &teloxide::types::Message, bot_name: &str) -> Vec<Result<Self, teloxide::utils::command::ParseError>> {
+              use teloxide::types::MessageEntityKind;
+
+              let (text, entities) = match Self::__text_and_entities(message) {
+                  Some(text_and_entities) => text_and_entities,
+                  None => return Vec::new(),
+              };
+
+              let commands = entities
+                  .iter()
+                  .filter(|e| matches!(e.kind, MessageEntityKind::BotCommand))
+                  .collect::<Vec<_>>();
+
+              let mut res = Vec::new();
+              #unprefixed
+
+              for (i, command) in commands.iter().enumerate() {
+                  let end = commands
+                      .get(i + 1)
+                      .map_or(text.len(), |next| Self::__byte_offset(text, next.offset));
+
+                  if let Some(parsed) = Self::__parse_entity(text, entities, command, end, bot_name).transpose() {
+                      res.push(parsed);
+                  }
+              }
+
+              res
+         }
+
+         fn __text_and_entities(message: &teloxide::types::Message) -> Option<(&str, &[teloxide::types::MessageEntity])> {
+              let (text, entities) = match (message.text(), message.caption()) {
+                  (Some(text), _) => (text, message.entities()),
+                  (None, Some(caption)) => (caption, message.caption_entities()),
+                  (None, None) => return None,
+              };
+
+              Some((text, entities.unwrap_or(&[])))
+         }
+
+         /// Converts an offset in UTF-16 code units (used by entities) into
+         /// a byte offset in `text`.
+         fn __byte_offset(text: &str, utf16_offset: usize) -> usize {
+              let mut units = 0;
+              for (i, c) in text.char_indices() {
+                  if units >= utf16_offset {
+                      return i;
+                  }
+                  units += c.len_utf16();
+              }
+              text.len()
+         }
+
+         /// Parses a command marked by the `command` entity, with arguments
+         /// lasting until the byte offset `end`.
+         fn __parse_entity(
+             text: &str,
+             entities: &[teloxide::types::MessageEntity],
+             command: &teloxide::types::MessageEntity,
+             end: usize,
+             bot_name: &str,
+         ) -> Result<Option<Self>, teloxide::utils::command::ParseError> {
+              use teloxide::types::MessageEntityKind;
+
+              let start = Self::__byte_offset(text, command.offset);
+              let command_end = Self::__byte_offset(text, command.offset + command.length);
+              let full_command = &text[start..command_end];
+              let args = text[command_end..end].trim();
+              let args_start = end - text[command_end..end].trim_start().len();
+
+              let mentions = entities
+                  .iter()
+                  .filter_map(|e| {
+                      let user = match &e.kind {
+                          MessageEntityKind::Mention => None,
+                          MessageEntityKind::TextMention { user } => Some(user),
+                          _ => return None,
+                      };
+                      let start = Self::__byte_offset(text, e.offset).checked_sub(args_start)?;
+                      let end = Self::__byte_offset(text, e.offset + e.length) - args_start;
+                      (end <= args.len()).then_some((start..end, user))
+                  })
+                  .collect::<Vec<_>>();
+
+              Self::__parse_parts(full_command, args, &[bot_name], None, #case_insensitive, &mentions)
+         }
+    }
+}
+
+fn impl_parse_all(
+    infos: &[Command],
+    global: &CommandEnum,
+) -> proc_macro2::TokenStream {
+    let mut prefixes = infos
+        .iter()
+        .filter(|c| !c.skip)
+        .flat_map(|c| &c.prefixes)
+        .collect::<Vec<_>>();
+    prefixes.sort();
+    prefixes.dedup();
+    let not_addressed = not_addressed_doc(global.mention, "are skipped");
+
+    let unprefixed = match global.unprefixed {
+        Unprefixed::Skip => quote! { None },
+        Unprefixed::Report => quote! {
+            // Unwrap: split iterators always have at least one item
+            Some(Err(teloxide::utils::command::ParseError::UnknownCommand(
+                line.split(char::is_whitespace).next().unwrap().to_owned(),
+            )))
+        },
+    };
+
+    quote! {
+         /// Parses commands from each non-empty line of `s`.
+         ///
+         #[doc = #not_addressed]
+         pub fn parse_all(s: &str, bot_name: &str) -> Vec<Result<Self, teloxide::utils::command::ParseError>> {
+              let prefixes: &[&str] = &[#(#prefixes),*];
+
+              s.lines()
+                  .map(str::trim)
+                  .filter(|line| !line.is_empty())
+                  .filter_map(|line| {
+                      if let Some(command) = Self::__parse_trigger(line).transpose() {
+                          return Some(command);
+                      }
+                      if !prefixes.iter().any(|p| line.starts_with(p)) {
+                          return #unprefixed;
+                      }
+
+                      Self::parse_addressed(line, bot_name).transpose()
+                  })
+                  .collect()
+         }
+    }
+}
+
+fn impl_parse_parts(
+    infos: &[Command],
+    variants_initialization: &[proc_macro2::TokenStream],
+    global: &CommandEnum,
+) -> proc_macro2::TokenStream {
+    let mut all_prefixes = infos
+        .iter()
+        .filter(|c| !c.skip)
+        .flat_map(|c| &c.prefixes)
+        .collect::<Vec<_>>();
+    all_prefixes.sort();
+    all_prefixes.dedup();
+
+    let exact = infos
+        .iter()
+        .zip(variants_initialization)
+        .filter(|(c, _)| !c.skip && c.pattern.is_none() && c.trigger.is_none())
+        .enumerate()
+        .collect::<Vec<_>>();
+    let lookup_exact = exact.iter().map(|(i, (c, _))| {
+        let name = &c.name;
+        let check = c.impl_prefix_check();
+        quote! { #name if #check => Some(#i), }
+    });
+    let folding = global.case_insensitive.unwrap_or(CaseFolding::Ascii);
+    let lookup_folded = exact.iter().map(|(i, (c, _))| {
+        let name = folding.fold(&c.name);
+        let check = c.impl_prefix_check();
+        quote! { #name if #check => Some(#i), }
+    });
+    let fold_name = folding.impl_fold(quote! { name });
+    let exact_indices = exact.iter().map(|(i, _)| i);
+    let exact_initialization = exact.iter().map(|(_, (_, init))| init);
+    let abbreviated = global.abbreviations.map(|_| {
+        quote! {
+            let found = match found {
+                Some(i) => Some(i),
+                None => match &Self::__abbreviation_candidates(prefix, name, own_prefixes, case_insensitive)[..] {
+                    [] => None,
+                    [(i, _)] => Some(*i),
+                    candidates => {
+                        let candidates = candidates
+                            .iter()
+                            .map(|(_, n)| format!("{prefix}{n}"))
+                            .collect::<Vec<_>>();
+                        return Err(ParseError::Custom(
+                            format!(
+                                "Ambiguous command {command}, candidates: {}",
+                                candidates.join(", "),
+                            )
+                            .into(),
+                        ));
+                    }
+                },
+            };
+        }
+    });
+    let fn_abbreviation_candidates = global.abbreviations.map(|min| {
+        let names = exact.iter().map(|(_, (c, _))| &c.name);
+        let folded_names = exact.iter().map(|(_, (c, _))| folding.fold(&c.name));
+        let checks = exact.iter().map(|(_, (c, _))| c.impl_prefix_check());
+        let own_case_insensitive = global.case_insensitive.is_some();
+
+        quote! {
+             /// Returns the commands (with their prefixes) which `command`
+             /// (e.g. `/sta`) is an abbreviation of.
+             ///
+             /// `parse` accepts an abbreviation with a single candidate and
+             /// reports one with several candidates as ambiguous, unless it
+             /// is a full name of a command, which always takes precedence.
+             pub fn abbreviation_candidates(command: &str) -> Vec<String> {
+                  let mut prefixes = [#(#all_prefixes),*];
+                  prefixes.sort_by_key(|p| std::cmp::Reverse(p.len()));
+
+                  for prefix in prefixes {
+                      let name = match command.strip_prefix(prefix) {
+                          Some(name) => name,
+                          None => continue,
+                      };
+
+                      let candidates = Self::__abbreviation_candidates(prefix, name, true, #own_case_insensitive);
+                      if !candidates.is_empty() {
+                          return candidates
+                              .into_iter()
+                              .map(|(_, n)| format!("{prefix}{n}"))
+                              .collect();
+                      }
+                  }
+
+                  Vec::new()
+             }
+
+             /// Returns indices (among commands matched by name) and names
+             /// of the commands which `name` with `prefix` abbreviates.
+             fn __abbreviation_candidates(
+                 prefix: &str,
+                 name: &str,
+                 own_prefixes: bool,
+                 case_insensitive: bool,
+             ) -> Vec<(usize, &'static str)> {
+                  if name.chars().count() < #min {
+                      return Vec::new();
+                  }
+
+                  let names: &[&'static str] = &[#(#names),*];
+                  let (abbreviation, candidates): (std::borrow::Cow<str>, &[&str]) = match case_insensitive {
+                      false => (name.into(), names),
+                      true => (#fold_name.into(), &[#(#folded_names),*]),
+                  };
+                  candidates
+                      .iter()
+                      .zip([#(#checks),*])
+                      .enumerate()
+                      .filter(|(_, (n, check))| *check && n.starts_with(&*abbreviation))
+                      .map(|(i, _)| (i, names[i]))
+                      .collect()
+             }
+        }
+    });
+    let exact_matches = match exact.is_empty() {
+        true => quote! {},
+        false => quote! {
+            let found = match case_insensitive {
+                false => match name {
+                    #(#lookup_exact)*
+                    _ => None,
+                },
+                true => match &*#fold_name {
+                    #(#lookup_folded)*
+                    _ => None,
+                },
+            };
+            #abbreviated
+            if let Some(i) = found {
+                return Ok(Some(match i {
+                    #( #exact_indices => #exact_initialization, )*
+                    _ => unreachable!(),
+                }));
+            }
+        },
+    };
+
+    let pattern_matches = infos
+        .iter()
+        .zip(variants_initialization)
+        .filter(|(c, _)| !c.skip && c.pattern.is_some())
+        .map(|(_, init)| init)
+        .collect::<Vec<_>>();
+    let fn_match_pattern = match pattern_matches.is_empty() {
+        true => quote! {},
+        false => impl_match_pattern(),
+    };
+
+    let not_mentioned = match global.mention {
+        MentionMode::Required => quote! { return Ok(None) },
+        MentionMode::Optional | MentionMode::IgnoreOthers => quote! {},
+    };
+    let other_mentioned = match global.mention {
+        MentionMode::Optional => {
+            quote! { Some(n) => return Err(ParseError::WrongBotName(n.to_owned())) }
+        }
+        MentionMode::Required | MentionMode::IgnoreOthers => {
+            quote! { Some(_) => return Ok(None) }
+        }
+    };
+
+    quote! {
+         /// Parses a command from its parts: `full_command` (`/command` or
+         /// `/command@botname`) and `args`, with byte ranges of `mentions` in
+         /// `args`.
+         ///
+         /// The command can be addressed to any of `bot_names`. `prefixes`,
+         /// if set, replace prefixes of all commands.
+         #[allow(unused_variables)]
+         fn __parse_parts(
+             full_command: &str,
+             args: &str,
+             bot_names: &[&str],
+             prefixes: Option<&[&str]>,
+             case_insensitive: bool,
+             mentions: &[(std::ops::Range<usize>, Option<&teloxide::types::User>)],
+         ) -> Result<Option<Self>, teloxide::utils::command::ParseError> {
               // FIXME: we should probably just call a helper function from `teloxide`, instead of parsing command syntax ourselves
               use std::str::FromStr;
               use teloxide::utils::command::ParseError;
 
-              // 2 is used to only split once (=> in two parts),
-              // we only need to split the command and the rest of arguments.
-              let mut words = s.splitn(2, ' ');
-
               // Unwrap: split iterators always have at least one item
-              let mut full_command = words.next().unwrap().split('@');
+              let mut full_command = full_command.split('@');
               let command = full_command.next().unwrap();
 
               let bot_username = full_command.next();
               match bot_username {
-                  None => {}
-                  Some(username) if username.eq_ignore_ascii_case(bot_name) => {}
-                  Some(n) => return Err(ParseError::WrongBotName(n.to_owned())),
+                  None => { #not_mentioned }
+                  Some(username) if bot_names.iter().any(|n| username.eq_ignore_ascii_case(n)) => {}
+                  #other_mentioned,
               }
 
-              let args = words.next().unwrap_or("").to_owned();
-              match command {
-                   #(
-                        #matching_values => Ok(#variants_initialization),
-                   )*
-                   _ => Err(ParseError::UnknownCommand(command.to_owned())),
+              // Several prefixes can match (e.g. `!` and `!!` in `!!ban`), in
+              // which case longer prefixes are tried first.
+              let own_prefixes = prefixes.is_none();
+              let mut prefixes = prefixes.unwrap_or(&[#(#all_prefixes),*]).to_vec();
+              prefixes.sort_by_key(|p| std::cmp::Reverse(p.len()));
+
+              let args = args.to_owned();
+              for prefix in prefixes {
+                  let name = match command.strip_prefix(prefix) {
+                      Some(name) => name,
+                      None => continue,
+                  };
+
+                  #exact_matches
+
+                  #(#pattern_matches)*
               }
+
+              Err(ParseError::UnknownCommand(command.to_owned()))
          }
+
+         #fn_match_pattern
+
+         #fn_abbreviation_candidates
     }
 }
 
+/// Checks that no two commands are the same with their prefixes (or have the
+/// same keyword), taking case folding into account, since only one of them
+/// could be parsed.
+fn check_duplicates<'a>(
+    variants: impl Iterator<Item = &'a syn::Variant>,
+    infos: &[Command],
+    global: &CommandEnum,
+) -> Result<()> {
+    let mut seen =
+        Vec::<(bool, String, Option<CaseFolding>, &syn::Ident)>::new();
+    for (variant, command) in variants.zip(infos) {
+        if command.skip || command.pattern.is_some() {
+            continue;
+        }
+
+        let (is_trigger, folding, commands) = match &command.trigger {
+            Some(trigger) => (
+                true,
+                trigger.case_insensitive.then(|| {
+                    global.case_insensitive.unwrap_or(CaseFolding::Ascii)
+                }),
+                vec![command.name.clone()],
+            ),
+            None => (
+                false,
+                global.case_insensitive,
+                command.all_prefixed_commands(),
+            ),
+        };
+
+        for full in commands {
+            let duplicate = seen.iter().find(|(t, other, f, ident)| {
+                *t == is_trigger
+                    && *ident != &variant.ident
+                    && (*other == full
+                        || f.or(folding)
+                            .is_some_and(|f| f.fold(other) == f.fold(&full)))
+            });
+
+            if let Some((_, other_full, _, other)) = duplicate {
+                let error = compile_error_at(
+                    &format!(
+                        "command `{full}` of `{}` duplicates the command \
+                         `{other_full}` of `{other}`",
+                        variant.ident,
+                    ),
+                    variant.ident.span(),
+                );
+                let note = compile_error_at(
+                    &format!(
+                        "command `{other_full}` of `{other}` is duplicated by \
+                         `{full}` of `{}`",
+                        variant.ident,
+                    ),
+                    other.span(),
+                );
+                return Err(error.combine(note));
+            }
+            seen.push((is_trigger, full, folding, &variant.ident));
+        }
+    }
+
+    Ok(())
+}
+
+/// Maximum number of commands in the Telegram menu.
+const MAX_MENU_COMMANDS: usize = 100;
+
+/// Checks that commands in the menu (with the `/` prefix) satisfy the rules of
+/// `setMyCommands`, so `bot_commands` doesn't fail at runtime, if requested
+/// via `#[command(check_menu)]`.
+fn check_menu_rules<'a>(
+    variants: impl Iterator<Item = &'a syn::Variant>,
+    infos: &[Command],
+) -> Result<()> {
+    let menu =
+        variants.zip(infos).filter(|(_, c)| c.is_in_menu() && c.prefix == "/");
+
+    for (i, (variant, command)) in menu.enumerate() {
+        let error = |message: String| {
+            Err(compile_error_at(
+                &format!(
+                    "{message} (add `#[command(text_only)]` if the command is \
+                     not a part of the menu)"
+                ),
+                variant.ident.span(),
+            ))
+        };
+
+        if i == MAX_MENU_COMMANDS {
+            return error(format!(
+                "Telegram menu can't have more than {MAX_MENU_COMMANDS} \
+                 commands"
+            ));
+        }
+
+        let name = &command.name;
+        if !(1..=32).contains(&name.len())
+            || !name
+                .bytes()
+                .all(|b| matches!(b, b'a'..=b'z' | b'0'..=b'9' | b'_'))
+        {
+            return error(format!(
+                "command name `{name}` is not valid for Telegram, it must be \
+                 1-32 characters of `a-z`, `0-9` and `_`"
+            ));
+        }
+
+        // Commands without a description are not rejected, since they are
+        // common in enums which are never registered in the menu
+        if let Some(description) = &command.description {
+            if !(1..=256).contains(&description.chars().count()) {
+                return error(format!(
+                    "description of command `{name}` is not valid for \
+                     Telegram, it must be 1-256 characters long"
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
 fn get_enum_data(input: &DeriveInput) -> Result<&syn::DataEnum> {
     match &input.data {
         syn::Data::Enum(data) => Ok(data),
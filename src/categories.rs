@@ -0,0 +1,155 @@
+use quote::quote;
+
+use crate::{arguments::help_usage_of, command::Command};
+
+/// Returns `commands` in the order of the help text and the menu.
+///
+/// Commands are grouped by category: the default group (commands without a
+/// category) goes first, then categories in the order of their first
+/// commands. Within a group, commands with `order` go first, sorted by it,
+/// then the rest of them in declaration order.
+pub(crate) fn help_order<'a>(
+    commands: impl IntoIterator<Item = &'a Command>,
+) -> Vec<&'a Command> {
+    let mut commands = commands.into_iter().collect::<Vec<_>>();
+    commands.sort_by_key(|c| (c.order.is_none(), c.order));
+
+    let mut categories = Vec::new();
+    for category in commands.iter().filter_map(|c| c.category.as_ref()) {
+        if !categories.contains(&category) {
+            categories.push(category);
+        }
+    }
+    commands.sort_by_key(|c| {
+        c.category.as_ref().map(|c| categories.iter().position(|&x| x == c))
+    });
+
+    commands
+}
+
+/// Returns prefixes of `command` with the heading of its category, used when
+/// it's the first command of the category: for the start of the help text
+/// and for the rest of it (separated from the previous group).
+///
+/// `CommandDescriptions` can't show headings by themselves, so they are a
+/// part of the prefix.
+fn headed_prefixes(command: &Command) -> Option<[String; 2]> {
+    let category = command.category.as_ref()?;
+    let prefix = &command.prefix;
+
+    Some([format!("{category}\n{prefix}"), format!("\n{category}\n{prefix}")])
+}
+
+/// Returns code which constructs the description of `command` with `prefix`.
+fn impl_description(
+    command: &Command,
+    prefix: &str,
+) -> proc_macro2::TokenStream {
+    let usage = help_usage_of(command);
+    let description = command.description.as_deref().unwrap_or_default();
+
+    quote! {
+        CommandDescription { prefix: #prefix, command: #usage, description: #description }
+    }
+}
+
+/// Returns code of a `'static` slice of descriptions of all commands in the
+/// help text, with headings of categories known at compile time.
+pub(crate) fn impl_all_descriptions(
+    infos: &[Command],
+) -> proc_macro2::TokenStream {
+    let mut last_category = None;
+    let descriptions =
+        help_order(infos.iter().filter(|command| command.is_in_help()))
+            .into_iter()
+            .enumerate()
+            .map(|(i, command)| {
+                // The first command of a category shows its heading
+                let prefix = match headed_prefixes(command) {
+                    Some([first, later])
+                        if command.category != last_category =>
+                    {
+                        if i == 0 {
+                            first
+                        } else {
+                            later
+                        }
+                    }
+                    _ => command.prefix.clone(),
+                };
+                last_category = command.category.clone();
+
+                impl_description(command, &prefix)
+            })
+            .collect::<Vec<_>>();
+
+    quote! { &[#(#descriptions),*] }
+}
+
+/// Generates `__visible_descriptions`, which returns descriptions of the
+/// commands in the help text whose flags in `visible` are set, with headings
+/// of categories before the first visible command of each of them.
+///
+/// It's used by `descriptions_for`, where headings depend on the visible
+/// commands, while `descriptions` uses `impl_all_descriptions`.
+pub(crate) fn impl_visible_descriptions(
+    infos: &[Command],
+) -> proc_macro2::TokenStream {
+    let infos = help_order(infos.iter().filter(|command| command.is_in_help()));
+    let len = infos.len();
+    let entries = infos.iter().map(|command| {
+        let Command { prefix, category, .. } = command;
+        let category = match category {
+            Some(category) => quote! { Some(#category) },
+            None => quote! { None },
+        };
+        let [first, later] = headed_prefixes(command)
+            .unwrap_or_else(|| [prefix.clone(), prefix.clone()]);
+        let description = impl_description(command, prefix);
+
+        quote! { (#category, [#first, #later], #description) }
+    });
+
+    quote! {
+        /// Returns descriptions of the commands in the help text, whose
+        /// flags in `visible` are set.
+        fn __visible_descriptions(visible: Vec<bool>) -> &'static [teloxide::utils::command::CommandDescription<'static>] {
+            use teloxide::utils::command::CommandDescription;
+            use std::sync::Mutex;
+
+            // `CommandDescriptions` needs a `'static` slice, so descriptions
+            // are leaked, but only once per distinct set of visible commands
+            // (and there are at most `commands + 1` of them).
+            #[allow(clippy::type_complexity)]
+            static INTERNED: Mutex<Vec<(Vec<bool>, &'static [CommandDescription<'static>])>> =
+                Mutex::new(Vec::new());
+
+            let mut interned = INTERNED.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(&(_, descriptions)) = interned.iter().find(|(v, _)| *v == visible) {
+                return descriptions;
+            }
+
+            let entries: [(Option<&str>, [&'static str; 2], CommandDescription<'static>); #len] = [#(#entries),*];
+            let mut last_category = None;
+            let descriptions: &'static [_] = Vec::leak(
+                entries
+                    .into_iter()
+                    .zip(&visible)
+                    .filter_map(|(d, &v)| v.then_some(d))
+                    .enumerate()
+                    .map(|(i, (category, [first, later], mut description))| {
+                        // The first visible command of a category shows its
+                        // heading
+                        if category.is_some() && category != last_category {
+                            description.prefix = if i == 0 { first } else { later };
+                        }
+                        last_category = category;
+                        description
+                    })
+                    .collect(),
+            );
+            interned.push((visible, descriptions));
+            descriptions
+        }
+    }
+}
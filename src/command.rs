@@ -1,23 +1,69 @@
 use crate::{
-    command_attr::CommandAttrs, command_enum::CommandEnum,
-    error::compile_error_at, fields_parse::ParserType, Result,
+    arguments::Argument,
+    command_attr::{CommandAttrs, MetaField},
+    command_enum::CommandEnum,
+    error::compile_error_at,
+    fields_parse::ParserType,
+    pattern::Pattern,
+    trigger::{Trigger, TriggerMatch},
+    Result,
 };
 
 pub(crate) struct Command {
-    /// Prefix of this command, for example "/".
+    /// Primary prefix of this command, for example "/".
     pub prefix: String,
+    /// All prefixes of this command, starting with the primary one.
+    pub prefixes: Vec<String>,
     /// Description for the command.
     pub description: Option<String>,
     /// Name of the command, with all renames already applied.
     pub name: String,
     /// Parser for arguments of this command.
     pub parser: ParserType,
+    /// User-defined metadata of this command, see `meta_type`.
+    pub meta: Vec<MetaField>,
+    /// Permission level required to use this command, see `permission_type`.
+    pub permission: Option<syn::Expr>,
+    /// Handler function of this command, used by the generated `dispatch`.
+    pub handler: Option<syn::Path>,
+    /// Pattern of this command, if fields are parsed from its name instead of
+    /// arguments. `name` is the source of the pattern then.
+    pub pattern: Option<Pattern>,
+    /// Keyword trigger of this command, if it doesn't have a prefix. `name`
+    /// is the keyword then.
+    pub trigger: Option<Trigger>,
+    /// Whether the command is typed as text only, so it's not a part of the
+    /// Telegram menu.
+    pub text_only: bool,
+    /// Whether the command is hidden from the Telegram menu
+    /// (`bot_commands`).
+    pub hide_from_menu: bool,
+    /// Whether the command is hidden from the help text (`descriptions`).
+    pub hide_from_help: bool,
+    /// Span of the deprecated `description = "off"`, if it's used to hide
+    /// the command.
+    pub description_off: Option<proc_macro2::Span>,
+    /// Whether the variant is skipped, so it's never parsed and is not a
+    /// part of the menu or the help text.
+    pub skip: bool,
+    /// Hint for users of this command, if it's deprecated (e.g. "use
+    /// /newname"). Deprecated commands are hidden, but still parsed.
+    pub deprecated: Option<String>,
+    /// Arguments of this command, i.e. fields of the variant.
+    pub arguments: Vec<Argument>,
+    /// Category of this command in the help text, commands without a
+    /// category are in the default group.
+    pub category: Option<String>,
+    /// Position of this command in the help text and the menu, commands
+    /// without it go after the ordered ones.
+    pub order: Option<usize>,
 }
 
 impl Command {
     pub fn new(
         name: &str,
         attributes: &[syn::Attribute],
+        fields: &syn::Fields,
         global_options: &CommandEnum,
     ) -> Result<Self> {
         let attrs = CommandAttrs::from_attributes(attributes)?;
@@ -27,31 +73,297 @@ impl Command {
             rename_rule,
             rename,
             parser,
-            // FIXME: error on/do not ignore separator
-            separator: _,
+            separator,
+            collapse_separators,
+            kind,
+            meta_type,
+            meta,
+            permission_type,
+            permission,
+            handler,
+            handler_context,
+            handler_output,
+            handler_exhaustive,
+            mention,
+            unprefixed,
+            deep_link,
+            pattern,
+            case_insensitive,
+            parse_options,
+            trigger,
+            trigger_match,
+            trigger_case_insensitive,
+            abbreviations,
+            text_only,
+            check_menu,
+            hide,
+            hide_from_menu,
+            hide_from_help,
+            skip,
+            deprecated,
+            arguments,
+            category,
+            order,
         } = attrs;
 
-        let name = match (rename, rename_rule) {
-            (Some((rename, _)), None) => rename,
-            (Some(_), Some((_, sp))) => {
+        if let Some((_kind, sp)) = kind {
+            return Err(compile_error_at(
+                "`kind` attribute can only be applied to enums, not to their \
+                 variants",
+                sp,
+            ));
+        }
+
+        if let Some((_meta_type, sp)) = meta_type {
+            return Err(compile_error_at(
+                "`meta_type` attribute can only be applied to enums, not to \
+                 their variants",
+                sp,
+            ));
+        }
+
+        if let Some((_permission_type, sp)) = permission_type {
+            return Err(compile_error_at(
+                "`permission_type` attribute can only be applied to enums, \
+                 not to their variants",
+                sp,
+            ));
+        }
+
+        let enum_only = [
+            handler_context.map(|(_, sp)| ("handler_context", sp)),
+            handler_output.map(|(_, sp)| ("handler_output", sp)),
+            handler_exhaustive.map(|(_, sp)| ("handler_exhaustive", sp)),
+            mention.map(|(_, sp)| ("mention", sp)),
+            unprefixed.map(|(_, sp)| ("unprefixed", sp)),
+            case_insensitive.map(|(_, sp)| ("case_insensitive", sp)),
+            parse_options.map(|(_, sp)| ("parse_options", sp)),
+            abbreviations.map(|(_, sp)| ("abbreviations", sp)),
+            arguments.map(|(_, sp)| ("arguments", sp)),
+            check_menu.map(|(_, sp)| ("check_menu", sp)),
+        ];
+        if let Some((name, sp)) = enum_only.into_iter().flatten().next() {
+            return Err(compile_error_at(
+                &format!(
+                    "`{name}` attribute can only be applied to enums, not to \
+                     their variants"
+                ),
+                sp,
+            ));
+        }
+
+        let handler = match (handler, &global_options.handler_context) {
+            (Some((handler, _)), Some(_)) => Some(handler),
+            (Some((_, sp)), None) => {
+                return Err(compile_error_at(
+                    "`handler` attribute requires `#[command(handler_context \
+                     = ...)]` on the enum",
+                    sp,
+                ))
+            }
+            (None, _) => None,
+        };
+
+        let permission = match (permission, &global_options.permission_type) {
+            (Some((permission, _)), Some(_)) => Some(permission),
+            (Some((_, sp)), None) => {
+                return Err(compile_error_at(
+                    "`permission` attribute requires \
+                     `#[command(permission_type = ...)]` on the enum",
+                    sp,
+                ))
+            }
+            (None, _) => None,
+        };
+
+        let meta = match (meta, &global_options.meta_type) {
+            (Some((meta, _)), Some(_)) => meta,
+            (Some((_, sp)), None) => {
+                return Err(compile_error_at(
+                    "`meta` attribute requires `#[command(meta_type = ...)]` \
+                     on the enum",
+                    sp,
+                ))
+            }
+            (None, _) => Vec::new(),
+        };
+
+        if skip.is_some() {
+            let conflicting = [
+                description.as_ref().map(|(_, sp)| *sp),
+                parser.as_ref().map(|(_, sp)| *sp),
+                pattern.as_ref().map(|(_, sp)| *sp),
+                trigger.as_ref().map(|(_, sp)| *sp),
+                deep_link.as_ref().map(|(_, sp)| *sp),
+                hide.map(|(_, sp)| sp),
+                hide_from_menu.map(|(_, sp)| sp),
+                hide_from_help.map(|(_, sp)| sp),
+                deprecated.as_ref().map(|(_, sp)| *sp),
+            ];
+            if let Some(sp) = conflicting.into_iter().flatten().next() {
+                return Err(compile_error_at(
+                    "`skip` can't be used together with `description`, \
+                     `parse_with`, `pattern`, `trigger`, `deep_link`, \
+                     `deprecated` or `hide` attributes",
+                    sp,
+                ));
+            }
+        }
+
+        let trigger = match (trigger, trigger_match, trigger_case_insensitive) {
+            (None, Some((_, sp)), _) | (None, _, Some((_, sp))) => {
+                return Err(compile_error_at(
+                    "this attribute requires `#[command(trigger = ...)]`",
+                    sp,
+                ))
+            }
+            (None, None, None) => None,
+            (Some((keyword, sp)), matching, case_insensitive) => {
+                let conflicting = [
+                    prefix.as_ref().map(|(_, sp)| *sp),
+                    rename.as_ref().map(|(_, sp)| *sp),
+                    rename_rule.as_ref().map(|(_, sp)| *sp),
+                    pattern.as_ref().map(|(_, sp)| *sp),
+                    deep_link.as_ref().map(|(_, sp)| *sp),
+                ];
+                if let Some(sp) = conflicting.into_iter().flatten().next() {
+                    return Err(compile_error_at(
+                        "`trigger` can't be used together with `prefix`, \
+                         `rename`, `rename_rule`, `pattern` or `deep_link`",
+                        sp,
+                    ));
+                }
+                if keyword.is_empty() || keyword.contains(char::is_whitespace) {
+                    return Err(compile_error_at(
+                        "`trigger` must be a single non-empty word",
+                        sp,
+                    ));
+                }
+
+                Some((
+                    keyword,
+                    Trigger {
+                        matching: matching
+                            .map(|(m, _)| m)
+                            .unwrap_or(TriggerMatch::FirstWord),
+                        case_insensitive: case_insensitive.is_some(),
+                    },
+                ))
+            }
+        };
+
+        let pattern = match (pattern, &rename, &parser) {
+            (Some(_), Some((_, sp)), _) | (Some(_), _, Some((_, sp))) => {
+                return Err(compile_error_at(
+                    "`pattern` can't be used together with `rename` or \
+                     `parse_with`",
+                    *sp,
+                ))
+            }
+            (Some((pattern, sp)), None, None) => {
+                Some(Pattern::parse(pattern, sp)?)
+            }
+            (None, ..) => None,
+        };
+
+        let (keyword, trigger) = trigger.unzip();
+        let name = match (&pattern, keyword, rename, rename_rule) {
+            (Some(pattern), ..) => pattern.source.clone(),
+            (None, Some(keyword), ..) => keyword,
+            (None, None, Some((rename, _)), None) => rename,
+            (None, None, Some(_), Some((_, sp))) => {
                 return Err(compile_error_at(
                     "`rename_rule` can't be applied to `rename`-d variant",
                     sp,
                 ))
             }
-            (None, Some((rule, _))) => rule.apply(name),
-            (None, None) => global_options.rename_rule.apply(name),
+            (None, None, None, Some((rule, _))) => rule.apply(name),
+            (None, None, None, None) => global_options.rename_rule.apply(name),
+        };
+
+        // Commands with a trigger don't have prefixes at all
+        let prefixes = match &trigger {
+            Some(_) => Vec::new(),
+            None => prefix
+                .map(|(p, _)| p)
+                .unwrap_or_else(|| global_options.prefixes.clone()),
+        };
+        let prefix = prefixes.first().cloned().unwrap_or_default();
+        // `description = "off"` is literal if visibility is explicit
+        let explicit_visibility = hide.is_some()
+            || hide_from_menu.is_some()
+            || hide_from_help.is_some();
+        let (description, description_off) = match description {
+            Some((d, sp)) if d == "off" && !explicit_visibility => {
+                (None, Some(sp))
+            }
+            description => (description.map(|(d, _)| d), None),
+        };
+        let hidden =
+            hide.is_some() || description_off.is_some() || deprecated.is_some();
+        let parser = match (parser, deep_link) {
+            (Some(_), Some((_, sp))) => {
+                return Err(compile_error_at(
+                    "`deep_link` can't be used together with `parse_with`",
+                    sp,
+                ))
+            }
+            (None, Some((codec, sp))) => {
+                if format!("{prefix}{name}") != "/start" {
+                    return Err(compile_error_at(
+                        "`deep_link` can only be applied to the `/start` \
+                         command",
+                        sp,
+                    ));
+                }
+
+                ParserType::DeepLink(codec)
+            }
+            (Some((p, _)), None) => p,
+            (None, None) => global_options.parser_type.clone(),
+        };
+        let parser = match parser {
+            ParserType::Split { separator: sep, collapse } => {
+                ParserType::Split {
+                    separator: separator.map(|(s, _)| s).or(sep),
+                    collapse: collapse || collapse_separators.is_some(),
+                }
+            }
+            parser => {
+                if let Some((_, sp)) = collapse_separators {
+                    return Err(compile_error_at(
+                        "`collapse_separators` requires `parse_with = \
+                         \"split\"`",
+                        sp,
+                    ));
+                }
+                parser
+            }
         };
 
-        let prefix = prefix
-            .map(|(p, _)| p)
-            .unwrap_or_else(|| global_options.prefix.clone());
-        let description = description.map(|(d, _)| d);
-        let parser = parser
-            .map(|(p, _)| p)
-            .unwrap_or_else(|| global_options.parser_type.clone());
+        let arguments = Argument::from_fields(fields, &parser)?;
 
-        Ok(Self { prefix, description, parser, name })
+        Ok(Self {
+            prefix,
+            prefixes,
+            description,
+            parser,
+            name,
+            meta,
+            permission,
+            handler,
+            pattern,
+            trigger,
+            text_only: text_only.is_some() || global_options.text_only,
+            hide_from_menu: hidden || hide_from_menu.is_some(),
+            hide_from_help: hidden || hide_from_help.is_some(),
+            description_off,
+            skip: skip.is_some(),
+            deprecated: deprecated.map(|(d, _)| d),
+            arguments,
+            category: category.map(|(c, _)| c),
+            order: order.map(|(o, _)| o),
+        })
     }
 
     pub fn get_prefixed_command(&self) -> String {
@@ -59,7 +371,36 @@ impl Command {
         format!("{prefix}{name}")
     }
 
-    pub(crate) fn description_is_enabled(&self) -> bool {
-        self.description != Some("off".to_owned())
+    /// Returns the name with each of the prefixes.
+    pub(crate) fn all_prefixed_commands(&self) -> Vec<String> {
+        self.prefixes.iter().map(|p| format!("{p}{}", self.name)).collect()
+    }
+
+    /// Returns code which checks that `prefix` is one of the prefixes of
+    /// this command, unless prefixes are overridden at runtime
+    /// (`own_prefixes` is `false`).
+    pub(crate) fn impl_prefix_check(&self) -> proc_macro2::TokenStream {
+        let prefixes = &self.prefixes;
+        quote::quote! { (!own_prefixes || matches!(prefix, #(#prefixes)|*)) }
+    }
+
+    /// Returns `true` if the command is hidden both from the menu and from
+    /// the help text.
+    pub(crate) fn is_hidden(&self) -> bool {
+        self.skip || (self.hide_from_menu && self.hide_from_help)
+    }
+
+    /// Returns `true` if the command can be a part of the Telegram menu.
+    pub(crate) fn is_in_menu(&self) -> bool {
+        !self.skip
+            && !self.hide_from_menu
+            && self.pattern.is_none()
+            && self.trigger.is_none()
+            && !self.text_only
+    }
+
+    /// Returns `true` if the command is a part of the help text.
+    pub(crate) fn is_in_help(&self) -> bool {
+        !self.skip && !self.hide_from_help
     }
 }
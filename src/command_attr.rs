@@ -1,22 +1,71 @@
 use crate::{
-    attr::{fold_attrs, Attr},
+    attr::{fold_attrs, Attr, AttrValue},
+    command_enum::{CaseFolding, MentionMode, Unprefixed},
+    deep_link::DeepLinkCodec,
     error::compile_error_at,
     fields_parse::ParserType,
     rename_rules::RenameRule,
+    trigger::TriggerMatch,
     Result,
 };
 
 use proc_macro2::Span;
-use syn::Attribute;
+use syn::{
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    Attribute, Expr, Ident, Path, Token, Type,
+};
 
 /// All attributes that can be used for `derive(BotCommands)`
 pub(crate) struct CommandAttrs {
-    pub prefix: Option<(String, Span)>,
+    pub prefix: Option<(Vec<String>, Span)>,
     pub description: Option<(String, Span)>,
     pub rename_rule: Option<(RenameRule, Span)>,
     pub rename: Option<(String, Span)>,
     pub parser: Option<(ParserType, Span)>,
     pub separator: Option<(String, Span)>,
+    pub collapse_separators: Option<((), Span)>,
+    pub kind: Option<(Ident, Span)>,
+    pub meta_type: Option<(Path, Span)>,
+    pub meta: Option<(Vec<MetaField>, Span)>,
+    pub permission_type: Option<(Path, Span)>,
+    pub permission: Option<(Expr, Span)>,
+    pub handler: Option<(Path, Span)>,
+    pub handler_context: Option<(Type, Span)>,
+    pub handler_output: Option<(Type, Span)>,
+    pub handler_exhaustive: Option<((), Span)>,
+    pub mention: Option<(MentionMode, Span)>,
+    pub unprefixed: Option<(Unprefixed, Span)>,
+    pub deep_link: Option<(DeepLinkCodec, Span)>,
+    pub pattern: Option<(String, Span)>,
+    pub case_insensitive: Option<(CaseFolding, Span)>,
+    pub parse_options: Option<(Ident, Span)>,
+    pub trigger: Option<(String, Span)>,
+    pub trigger_match: Option<(TriggerMatch, Span)>,
+    pub trigger_case_insensitive: Option<((), Span)>,
+    pub abbreviations: Option<(usize, Span)>,
+    pub text_only: Option<((), Span)>,
+    pub check_menu: Option<((), Span)>,
+    pub hide: Option<((), Span)>,
+    pub hide_from_menu: Option<((), Span)>,
+    pub hide_from_help: Option<((), Span)>,
+    pub skip: Option<((), Span)>,
+    pub deprecated: Option<(String, Span)>,
+    pub arguments: Option<(Ident, Span)>,
+    pub category: Option<(String, Span)>,
+    pub order: Option<(usize, Span)>,
+}
+
+/// A single field of `#[command(meta(...))]`.
+///
+/// For example:
+/// ```text
+///   #[command(meta(role = Role::Admin, audit = true))]
+///                  ^^^^^^^^^^^^^^^^^^  ^^^^^^^^^^^^
+/// ```
+pub(crate) struct MetaField {
+    pub name: Ident,
+    pub value: Expr,
 }
 
 /// A single k/v attribute for `BotCommands` derive macro.
@@ -35,12 +84,42 @@ struct CommandAttr {
 
 /// Kind of [`CommandAttr`].
 enum CommandAttrKind {
-    Prefix(String),
+    Prefix(Vec<String>),
     Description(String),
     RenameRule(RenameRule),
     Rename(String),
     ParseWith(ParserType),
     Separator(String),
+    CollapseSeparators,
+    Kind(Ident),
+    MetaType(Path),
+    Meta(Vec<MetaField>),
+    PermissionType(Path),
+    Permission(Expr),
+    Handler(Path),
+    HandlerContext(Type),
+    HandlerOutput(Type),
+    HandlerExhaustive,
+    Mention(MentionMode),
+    Unprefixed(Unprefixed),
+    DeepLink(DeepLinkCodec),
+    Pattern(String),
+    CaseInsensitive(CaseFolding),
+    ParseOptions(Ident),
+    Trigger(String),
+    TriggerMatch(TriggerMatch),
+    TriggerCaseInsensitive,
+    Abbreviations(usize),
+    TextOnly,
+    CheckMenu,
+    Hide,
+    HideFromMenu,
+    HideFromHelp,
+    Skip,
+    Deprecated(String),
+    Arguments(Ident),
+    Category(String),
+    Order(usize),
 }
 
 impl CommandAttrs {
@@ -58,6 +137,36 @@ impl CommandAttrs {
                 rename: None,
                 parser: None,
                 separator: None,
+                collapse_separators: None,
+                kind: None,
+                meta_type: None,
+                meta: None,
+                permission_type: None,
+                permission: None,
+                handler: None,
+                handler_context: None,
+                handler_output: None,
+                handler_exhaustive: None,
+                mention: None,
+                unprefixed: None,
+                deep_link: None,
+                pattern: None,
+                case_insensitive: None,
+                parse_options: None,
+                trigger: None,
+                trigger_match: None,
+                trigger_case_insensitive: None,
+                abbreviations: None,
+                text_only: None,
+                check_menu: None,
+                hide: None,
+                hide_from_menu: None,
+                hide_from_help: None,
+                skip: None,
+                deprecated: None,
+                arguments: None,
+                category: None,
+                order: None,
             },
             |mut this, attr| {
                 fn insert<T>(
@@ -83,6 +192,60 @@ impl CommandAttrs {
                     Rename(r) => insert(&mut this.rename, r, attr.sp),
                     ParseWith(p) => insert(&mut this.parser, p, attr.sp),
                     Separator(s) => insert(&mut this.separator, s, attr.sp),
+                    CollapseSeparators => {
+                        insert(&mut this.collapse_separators, (), attr.sp)
+                    }
+                    Kind(k) => insert(&mut this.kind, k, attr.sp),
+                    MetaType(m) => insert(&mut this.meta_type, m, attr.sp),
+                    Meta(m) => insert(&mut this.meta, m, attr.sp),
+                    PermissionType(p) => {
+                        insert(&mut this.permission_type, p, attr.sp)
+                    }
+                    Permission(p) => insert(&mut this.permission, p, attr.sp),
+                    Handler(h) => insert(&mut this.handler, h, attr.sp),
+                    HandlerContext(c) => {
+                        insert(&mut this.handler_context, c, attr.sp)
+                    }
+                    HandlerOutput(o) => {
+                        insert(&mut this.handler_output, o, attr.sp)
+                    }
+                    HandlerExhaustive => {
+                        insert(&mut this.handler_exhaustive, (), attr.sp)
+                    }
+                    Mention(m) => insert(&mut this.mention, m, attr.sp),
+                    Unprefixed(u) => insert(&mut this.unprefixed, u, attr.sp),
+                    DeepLink(d) => insert(&mut this.deep_link, d, attr.sp),
+                    Pattern(p) => insert(&mut this.pattern, p, attr.sp),
+                    CaseInsensitive(c) => {
+                        insert(&mut this.case_insensitive, c, attr.sp)
+                    }
+                    ParseOptions(o) => {
+                        insert(&mut this.parse_options, o, attr.sp)
+                    }
+                    Trigger(t) => insert(&mut this.trigger, t, attr.sp),
+                    TriggerMatch(m) => {
+                        insert(&mut this.trigger_match, m, attr.sp)
+                    }
+                    TriggerCaseInsensitive => {
+                        insert(&mut this.trigger_case_insensitive, (), attr.sp)
+                    }
+                    Abbreviations(a) => {
+                        insert(&mut this.abbreviations, a, attr.sp)
+                    }
+                    TextOnly => insert(&mut this.text_only, (), attr.sp),
+                    CheckMenu => insert(&mut this.check_menu, (), attr.sp),
+                    Hide => insert(&mut this.hide, (), attr.sp),
+                    HideFromMenu => {
+                        insert(&mut this.hide_from_menu, (), attr.sp)
+                    }
+                    HideFromHelp => {
+                        insert(&mut this.hide_from_help, (), attr.sp)
+                    }
+                    Skip => insert(&mut this.skip, (), attr.sp),
+                    Deprecated(d) => insert(&mut this.deprecated, d, attr.sp),
+                    Arguments(a) => insert(&mut this.arguments, a, attr.sp),
+                    Category(c) => insert(&mut this.category, c, attr.sp),
+                    Order(o) => insert(&mut this.order, o, attr.sp),
                 }?;
 
                 Ok(this)
@@ -98,7 +261,21 @@ impl CommandAttr {
         let sp = attr.span();
         let Attr { key, value } = attr;
         let kind = match &*key.to_string() {
-            "prefix" => Prefix(value.expect_string()?),
+            "prefix" => {
+                let prefixes = value.expect_strings()?;
+                if let Some(p) = prefixes
+                    .iter()
+                    .enumerate()
+                    .find_map(|(i, p)| prefixes[..i].contains(p).then_some(p))
+                {
+                    return Err(compile_error_at(
+                        &format!("duplicate prefix `{p}`"),
+                        sp,
+                    ));
+                }
+
+                Prefix(prefixes)
+            }
             "description" => Description(value.expect_string()?),
             "rename_rule" => RenameRule(
                 value
@@ -108,10 +285,101 @@ impl CommandAttr {
             "rename" => Rename(value.expect_string()?),
             "parse_with" => ParseWith(ParserType::parse(value)?),
             "separator" => Separator(value.expect_string()?),
+            "collapse_separators" => {
+                value.expect_none()?;
+                CollapseSeparators
+            }
+            "kind" => Kind(value.expect_ident()?),
+            "meta_type" => MetaType(value.expect_path()?),
+            "meta" => Meta(value.expect_args(MetaField::parse_list)?),
+            "permission_type" => PermissionType(value.expect_path()?),
+            "permission" => Permission(value.expect_expr()?),
+            "handler" => Handler(value.expect_path()?),
+            "handler_context" => HandlerContext(value.expect_type()?),
+            "handler_output" => HandlerOutput(value.expect_type()?),
+            "handler_exhaustive" => {
+                value.expect_none()?;
+                HandlerExhaustive
+            }
+            "mention" => Mention(
+                value.expect_string().and_then(|m| MentionMode::parse(&m))?,
+            ),
+            "deep_link" => DeepLink(DeepLinkCodec::parse(value)?),
+            "pattern" => Pattern(value.expect_string()?),
+            "case_insensitive" => CaseInsensitive(CaseFolding::parse(value)?),
+            "parse_options" => ParseOptions(value.expect_ident()?),
+            "trigger" => Trigger(value.expect_string()?),
+            "trigger_match" => TriggerMatch(
+                value
+                    .expect_string()
+                    .and_then(|m| self::TriggerMatch::parse(&m))?,
+            ),
+            "trigger_case_insensitive" => {
+                value.expect_none()?;
+                TriggerCaseInsensitive
+            }
+            "abbreviations" => Abbreviations(value.expect(
+                "nothing or a minimum length of abbreviations",
+                |v| match v {
+                    AttrValue::None(_) => Ok(1),
+                    AttrValue::Lit(syn::Lit::Int(ref l)) => {
+                        l.base10_parse().ok().filter(|&n| n > 0).ok_or(v)
+                    }
+                    _ => Err(v),
+                },
+            )?),
+            "text_only" => {
+                value.expect_none()?;
+                TextOnly
+            }
+            "check_menu" => {
+                value.expect_none()?;
+                CheckMenu
+            }
+            "hide" => {
+                value.expect_none()?;
+                Hide
+            }
+            "hide_from_menu" => {
+                value.expect_none()?;
+                HideFromMenu
+            }
+            "hide_from_help" => {
+                value.expect_none()?;
+                HideFromHelp
+            }
+            "skip" => {
+                value.expect_none()?;
+                Skip
+            }
+            "deprecated" => Deprecated(value.expect_string()?),
+            "arguments" => Arguments(value.expect_ident()?),
+            "category" => Category(value.expect_string()?),
+            "order" => Order(value.expect("an integer", |v| match v {
+                AttrValue::Lit(syn::Lit::Int(ref l)) => {
+                    l.base10_parse().map_err(|_| v)
+                }
+                _ => Err(v),
+            })?),
+            "unprefixed" => Unprefixed(
+                value
+                    .expect_string()
+                    .and_then(|u| self::Unprefixed::parse(&u))?,
+            ),
             _ => {
                 return Err(compile_error_at(
                     "unexpected attribute name (expected one of `prefix`, \
-                     `description`, `rename`, `parse_with` and `separator`",
+                     `description`, `rename_rule`, `rename`, `parse_with`, \
+                     `separator`, `collapse_separators`, `kind`, `meta_type`, \
+                     `meta`, `permission_type`, `permission`, `handler`, \
+                     `handler_context`, `handler_output`, \
+                     `handler_exhaustive`, `mention`, `unprefixed`, \
+                     `deep_link`, `pattern`, `case_insensitive`, \
+                     `parse_options`, `trigger`, `trigger_match`, \
+                     `trigger_case_insensitive`, `abbreviations`, \
+                     `text_only`, `check_menu`, `hide`, `hide_from_menu`, \
+                     `hide_from_help`, `skip`, `deprecated`, `arguments`, \
+                     `category` and `order`)",
                     key.span(),
                 ))
             }
@@ -121,7 +389,37 @@ impl CommandAttr {
     }
 }
 
-fn is_command_attribute(a: &Attribute) -> bool {
+impl MetaField {
+    fn parse_list(input: ParseStream) -> syn::Result<Vec<Self>> {
+        let fields = Punctuated::<Self, Token![,]>::parse_terminated(input)?;
+        let mut res: Vec<Self> = Vec::with_capacity(fields.len());
+
+        for field in fields {
+            if res.iter().any(|f| f.name == field.name) {
+                return Err(syn::Error::new(
+                    field.name.span(),
+                    "duplicate meta field",
+                ));
+            }
+
+            res.push(field);
+        }
+
+        Ok(res)
+    }
+}
+
+impl Parse for MetaField {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let value = input.parse()?;
+
+        Ok(Self { name, value })
+    }
+}
+
+pub(crate) fn is_command_attribute(a: &Attribute) -> bool {
     match a.path.get_ident() {
         Some(ident) => ident == "command",
         _ => false,
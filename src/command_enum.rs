@@ -1,13 +1,167 @@
 use crate::{
-    command_attr::CommandAttrs, error::compile_error_at,
-    fields_parse::ParserType, rename_rules::RenameRule, Result,
+    attr::AttrValue,
+    command_attr::CommandAttrs,
+    error::{compile_error, compile_error_at},
+    fields_parse::ParserType,
+    rename_rules::RenameRule,
+    Result,
 };
 
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Ident, Path, Type};
+
 pub(crate) struct CommandEnum {
-    pub prefix: String,
+    /// Prefixes of the commands, starting with the primary one, which is
+    /// used in descriptions and the menu.
+    pub prefixes: Vec<String>,
     pub description: Option<String>,
     pub rename_rule: RenameRule,
     pub parser_type: ParserType,
+    /// Name of the generated fieldless enum, if requested via
+    /// `#[command(kind = ...)]`.
+    pub kind: Option<Ident>,
+    /// Type of user-defined metadata, specified via
+    /// `#[command(meta_type = ...)]`.
+    pub meta_type: Option<Path>,
+    /// Type of permission levels, specified via
+    /// `#[command(permission_type = ...)]`.
+    pub permission_type: Option<Path>,
+    /// Type of the context passed to command handlers by the generated
+    /// `dispatch`.
+    pub handler_context: Option<Type>,
+    /// Output type of command handlers, `()` by default.
+    pub handler_output: Option<Type>,
+    /// Whether all variants must have a handler.
+    pub handler_exhaustive: bool,
+    /// How `@botname` mentions are treated.
+    pub mention: MentionMode,
+    /// How `parse_all` treats text which doesn't start with a prefix.
+    pub unprefixed: Unprefixed,
+    /// Case folding applied to command names before matching, if they are
+    /// case-insensitive.
+    pub case_insensitive: Option<CaseFolding>,
+    /// Name of the generated struct with runtime options of parsing, if
+    /// requested via `#[command(parse_options = ...)]`.
+    pub parse_options: Option<Ident>,
+    /// Minimum length of abbreviations of command names, if they are
+    /// accepted.
+    pub abbreviations: Option<usize>,
+    /// Whether commands are typed as text only and are not a part of the
+    /// Telegram menu.
+    pub text_only: bool,
+    /// Whether commands in the Telegram menu are checked against the rules
+    /// of `setMyCommands` at compile time.
+    pub check_menu: bool,
+    /// Name of the generated struct describing arguments of commands, if
+    /// requested via `#[command(arguments = ...)]`.
+    pub arguments: Option<Ident>,
+}
+
+/// Treatment of `@botname` mentions in commands (`/command@botname`).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum MentionMode {
+    /// Mention is optional, mentioning another bot is an error.
+    Optional,
+    /// Command must mention this bot, otherwise it's not addressed to us.
+    Required,
+    /// Mention is optional, commands mentioning another bot are not
+    /// addressed to us.
+    IgnoreOthers,
+}
+
+/// Treatment of text without a command prefix by `parse_all`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Unprefixed {
+    /// Text is skipped.
+    Skip,
+    /// Text is reported as an unknown command.
+    Report,
+}
+
+/// Case folding of case-insensitive commands, see
+/// `#[command(case_insensitive)]`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum CaseFolding {
+    /// Only ASCII letters are folded.
+    Ascii,
+    /// All letters are folded, according to Unicode.
+    Unicode,
+}
+
+impl CaseFolding {
+    pub fn parse(value: AttrValue) -> Result<Self> {
+        value.expect(r#"nothing, "ascii" or "unicode""#, |v| match v {
+            AttrValue::None(_) => Ok(Self::Ascii),
+            AttrValue::Lit(syn::Lit::Str(ref l)) => match &*l.value() {
+                "ascii" => Ok(Self::Ascii),
+                "unicode" => Ok(Self::Unicode),
+                _ => Err(v),
+            },
+            _ => Err(v),
+        })
+    }
+
+    /// Folds `s` the same way the generated code does.
+    pub fn fold(self, s: &str) -> String {
+        match self {
+            Self::Ascii => s.to_ascii_lowercase(),
+            Self::Unicode => s.to_lowercase(),
+        }
+    }
+
+    /// Returns code which checks that `s: &str` is equal to `other` after
+    /// folding.
+    pub fn impl_eq(self, s: TokenStream, other: &str) -> TokenStream {
+        match self {
+            Self::Ascii => quote! { #s.eq_ignore_ascii_case(#other) },
+            Self::Unicode => {
+                let other = self.fold(other);
+                quote! { #s.to_lowercase() == #other }
+            }
+        }
+    }
+
+    /// Returns code which folds `s: &str` into a `String`.
+    pub fn impl_fold(self, s: TokenStream) -> TokenStream {
+        match self {
+            Self::Ascii => quote! { #s.to_ascii_lowercase() },
+            Self::Unicode => quote! { #s.to_lowercase() },
+        }
+    }
+}
+
+impl Unprefixed {
+    pub fn parse(unprefixed: &str) -> Result<Self> {
+        match unprefixed {
+            "skip" => Ok(Self::Skip),
+            "report" => Ok(Self::Report),
+            invalid => Err(compile_error(format!(
+                "invalid `unprefixed` value `{invalid}` (supported values: \
+                 `skip` and `report`)"
+            ))),
+        }
+    }
+}
+
+impl MentionMode {
+    pub fn parse(mode: &str) -> Result<Self> {
+        use MentionMode::*;
+
+        let mode = match mode {
+            "optional" => Optional,
+            "required" => Required,
+            "ignore_others" => IgnoreOthers,
+            invalid => {
+                return Err(compile_error(format!(
+                    "invalid mention mode `{invalid}` (supported modes: \
+                     `optional`, `required` and `ignore_others`)"
+                )))
+            }
+        };
+
+        Ok(mode)
+    }
 }
 
 impl CommandEnum {
@@ -20,6 +174,36 @@ impl CommandEnum {
             rename,
             parser,
             separator,
+            collapse_separators,
+            kind,
+            meta_type,
+            meta,
+            permission_type,
+            permission,
+            handler,
+            handler_context,
+            handler_output,
+            handler_exhaustive,
+            mention,
+            unprefixed,
+            deep_link,
+            pattern,
+            case_insensitive,
+            parse_options,
+            trigger,
+            trigger_match,
+            trigger_case_insensitive,
+            abbreviations,
+            text_only,
+            check_menu,
+            hide,
+            hide_from_menu,
+            hide_from_help,
+            skip,
+            deprecated,
+            arguments,
+            category,
+            order,
         } = attrs;
 
         if let Some((_rename, sp)) = rename {
@@ -29,22 +213,125 @@ impl CommandEnum {
             ));
         }
 
+        if let Some((_meta, sp)) = meta {
+            return Err(compile_error_at(
+                "`meta` attribute can only be applied to enums *variants*",
+                sp,
+            ));
+        }
+
+        if let Some((_permission, sp)) = permission {
+            return Err(compile_error_at(
+                "`permission` attribute can only be applied to enums \
+                 *variants*",
+                sp,
+            ));
+        }
+
+        if let Some((_pattern, sp)) = pattern {
+            return Err(compile_error_at(
+                "`pattern` attribute can only be applied to enums *variants*",
+                sp,
+            ));
+        }
+
+        if let Some((_deep_link, sp)) = deep_link {
+            return Err(compile_error_at(
+                "`deep_link` attribute can only be applied to enums *variants*",
+                sp,
+            ));
+        }
+
+        let variant_only = [
+            trigger.map(|(_, sp)| ("trigger", sp)),
+            trigger_match.map(|(_, sp)| ("trigger_match", sp)),
+            trigger_case_insensitive
+                .map(|(_, sp)| ("trigger_case_insensitive", sp)),
+            hide.map(|(_, sp)| ("hide", sp)),
+            hide_from_menu.map(|(_, sp)| ("hide_from_menu", sp)),
+            hide_from_help.map(|(_, sp)| ("hide_from_help", sp)),
+            skip.map(|(_, sp)| ("skip", sp)),
+            deprecated.map(|(_, sp)| ("deprecated", sp)),
+            category.map(|(_, sp)| ("category", sp)),
+            order.map(|(_, sp)| ("order", sp)),
+        ];
+        if let Some((name, sp)) = variant_only.into_iter().flatten().next() {
+            return Err(compile_error_at(
+                &format!(
+                    "`{name}` attribute can only be applied to enums \
+                     *variants*"
+                ),
+                sp,
+            ));
+        }
+
+        if let Some((_handler, sp)) = handler {
+            return Err(compile_error_at(
+                "`handler` attribute can only be applied to enums *variants*",
+                sp,
+            ));
+        }
+
+        if handler_context.is_none() {
+            let dependent = [
+                handler_output.as_ref().map(|(_, sp)| ("handler_output", *sp)),
+                handler_exhaustive.map(|(_, sp)| ("handler_exhaustive", sp)),
+            ];
+            if let Some((name, sp)) = dependent.into_iter().flatten().next() {
+                return Err(compile_error_at(
+                    &format!(
+                        "`{name}` attribute requires \
+                         `#[command(handler_context = ...)]`"
+                    ),
+                    sp,
+                ));
+            }
+        }
+
         let mut parser = parser.map(|(p, _)| p).unwrap_or(ParserType::Default);
 
         // FIXME: Error on unused separator
-        if let (ParserType::Split { separator }, Some((s, _))) =
-            (&mut parser, &separator)
-        {
-            *separator = Some(s.clone())
+        match &mut parser {
+            ParserType::Split { separator: sep, collapse } => {
+                if let Some((s, _)) = &separator {
+                    *sep = Some(s.clone());
+                }
+                *collapse = collapse_separators.is_some();
+            }
+            _ => {
+                if let Some((_, sp)) = collapse_separators {
+                    return Err(compile_error_at(
+                        "`collapse_separators` requires `parse_with = \
+                         \"split\"`",
+                        sp,
+                    ));
+                }
+            }
         }
 
         Ok(Self {
-            prefix: prefix.map(|(p, _)| p).unwrap_or_else(|| "/".to_owned()),
+            prefixes: prefix
+                .map(|(p, _)| p)
+                .unwrap_or_else(|| vec!["/".to_owned()]),
             description: description.map(|(d, _)| d),
             rename_rule: rename_rule
                 .map(|(rr, _)| rr)
                 .unwrap_or(RenameRule::Identity),
             parser_type: parser,
+            kind: kind.map(|(k, _)| k),
+            meta_type: meta_type.map(|(m, _)| m),
+            permission_type: permission_type.map(|(p, _)| p),
+            handler_context: handler_context.map(|(c, _)| c),
+            handler_output: handler_output.map(|(o, _)| o),
+            handler_exhaustive: handler_exhaustive.is_some(),
+            mention: mention.map(|(m, _)| m).unwrap_or(MentionMode::Optional),
+            unprefixed: unprefixed.map(|(u, _)| u).unwrap_or(Unprefixed::Skip),
+            case_insensitive: case_insensitive.map(|(c, _)| c),
+            parse_options: parse_options.map(|(o, _)| o),
+            abbreviations: abbreviations.map(|(a, _)| a),
+            text_only: text_only.is_some(),
+            check_menu: check_menu.is_some(),
+            arguments: arguments.map(|(a, _)| a),
         })
     }
 }
@@ -0,0 +1,54 @@
+use crate::{
+    accessors::impl_accessors, command::Command, command_enum::CommandEnum,
+};
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Ident, Visibility};
+
+/// Generates a fieldless enum `kind` mirroring the variants of `type_name`,
+/// together with `kind` accessor for the command enum.
+pub(crate) fn impl_kind(
+    type_name: &Ident,
+    vis: &Visibility,
+    kind: &Ident,
+    variants: &[&Ident],
+    infos: &[Command],
+    global: &CommandEnum,
+) -> TokenStream {
+    let patterns =
+        variants.iter().map(|v| quote! { Self::#v }).collect::<Vec<_>>();
+    let accessors = impl_accessors(
+        quote! { self },
+        quote! { self },
+        &patterns,
+        infos,
+        global,
+    );
+
+    let kind_doc = format!("Fieldless kinds of [`{type_name}`] commands.");
+
+    quote! {
+        #[doc = #kind_doc]
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        #vis enum #kind {
+            #( #variants, )*
+        }
+
+        impl #kind {
+            /// All command kinds, in declaration order.
+            pub const ALL: &'static [Self] = &[#( Self::#variants ),*];
+
+            #accessors
+        }
+
+        impl #type_name {
+            /// Returns the kind of this command.
+            pub fn kind(&self) -> #kind {
+                match *self {
+                    #( Self::#variants { .. } => #kind::#variants, )*
+                }
+            }
+        }
+    }
+}
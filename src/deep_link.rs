@@ -0,0 +1,223 @@
+use crate::{
+    attr::AttrValue,
+    command::Command,
+    error::compile_error_at,
+    fields_parse::{option_inner, ParserType},
+    Result,
+};
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Ident, Type, Variant};
+
+/// Maximum length of a deep link payload allowed by Telegram.
+const MAX_PAYLOAD_LEN: usize = 64;
+
+/// Returns code which checks that `payload: &str` is a valid deep link
+/// payload: at most 64 characters of `A-Z`, `a-z`, `0-9`, `_` and `-`.
+fn impl_is_valid() -> TokenStream {
+    quote! {
+        payload.len() <= #MAX_PAYLOAD_LEN
+            && payload.bytes().all(|c| c.is_ascii_alphanumeric() || c == b'_' || c == b'-')
+    }
+}
+
+/// Encoding of a `/start` deep link payload, see `#[command(deep_link)]`.
+#[derive(Clone)]
+pub(crate) enum DeepLinkCodec {
+    /// Payload is used as-is, via `FromStr`/`Display`.
+    Raw,
+    /// Payload is the base64url-encoded `Display` string.
+    Base64,
+    /// Payload is converted via `path::encode`/`path::decode`.
+    Custom(syn::Path),
+}
+
+impl DeepLinkCodec {
+    pub fn parse(value: AttrValue) -> Result<Self> {
+        value.expect(
+            r#"nothing, "raw", "base64", or a path to a custom codec"#,
+            |v| match v {
+                AttrValue::None(_) => Ok(Self::Raw),
+                AttrValue::Path(p) => Ok(Self::Custom(p)),
+                AttrValue::Lit(syn::Lit::Str(ref l)) => match &*l.value() {
+                    "raw" => Ok(Self::Raw),
+                    "base64" => Ok(Self::Base64),
+                    _ => Err(v),
+                },
+                _ => Err(v),
+            },
+        )
+    }
+
+    /// Returns code which decodes `payload: &str` into `ty`, returning
+    /// `ParseError` on failure, including payloads which are not valid deep
+    /// link payloads.
+    pub fn decode(&self, ty: &Type) -> TokenStream {
+        let is_valid = impl_is_valid();
+        let decode = match self {
+            Self::Raw => quote! {
+                <#ty>::from_str(payload)
+                    .map_err(|e| ParseError::IncorrectFormat(e.into()))?
+            },
+            Self::Base64 => quote! {{
+                let mut bytes = Vec::with_capacity(payload.len() * 3 / 4);
+                let (mut acc, mut bits) = (0u32, 0);
+                for c in payload.bytes() {
+                    let sextet = match c {
+                        b'A'..=b'Z' => c - b'A',
+                        b'a'..=b'z' => c - b'a' + 26,
+                        b'0'..=b'9' => c - b'0' + 52,
+                        b'-' => 62,
+                        b'_' => 63,
+                        _ => {
+                            return Err(ParseError::IncorrectFormat(
+                                "invalid base64url deep link payload".into(),
+                            ))
+                        }
+                    };
+                    acc = (acc << 6) | u32::from(sextet);
+                    bits += 6;
+                    if bits >= 8 {
+                        bits -= 8;
+                        bytes.push((acc >> bits) as u8);
+                        acc &= (1 << bits) - 1;
+                    }
+                }
+
+                let payload = String::from_utf8(bytes)
+                    .map_err(|e| ParseError::IncorrectFormat(e.into()))?;
+                <#ty>::from_str(&payload)
+                    .map_err(|e| ParseError::IncorrectFormat(e.into()))?
+            }},
+            Self::Custom(path) => quote! {
+                #path::decode(payload)
+                    .map_err(|e| ParseError::IncorrectFormat(e.into()))?
+            },
+        };
+
+        quote! {{
+            if !(#is_valid) {
+                return Err(ParseError::IncorrectFormat(
+                    "deep link payload must be at most 64 characters of `A-Z`, `a-z`, `0-9`, `_` and `-`".into(),
+                ));
+            }
+            #decode
+        }}
+    }
+
+    /// Returns code which encodes `payload: &T` into a `String`.
+    fn encode(&self) -> TokenStream {
+        match self {
+            Self::Raw => quote! { payload.to_string() },
+            Self::Base64 => quote! {{
+                const ALPHABET: &[u8; 64] =
+                    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+                let mut res = String::new();
+                let (mut acc, mut bits) = (0u32, 0);
+                for byte in payload.to_string().bytes() {
+                    acc = (acc << 8) | u32::from(byte);
+                    bits += 8;
+                    while bits >= 6 {
+                        bits -= 6;
+                        res.push(ALPHABET[((acc >> bits) & 63) as usize] as char);
+                    }
+                    acc &= (1 << bits) - 1;
+                }
+                if bits > 0 {
+                    res.push(ALPHABET[((acc << (6 - bits)) & 63) as usize] as char);
+                }
+                res
+            }},
+            Self::Custom(path) => quote! { #path::encode(payload) },
+        }
+    }
+}
+
+/// Generates `deep_link` and `checked_deep_link_payload` for the variant
+/// marked with `#[command(deep_link)]`, if any.
+pub(crate) fn impl_deep_link<'a>(
+    type_name: &Ident,
+    variants: impl Iterator<Item = &'a Variant>,
+    infos: &[Command],
+) -> Result<Option<TokenStream>> {
+    let mut deep_links =
+        variants.zip(infos).filter_map(|(v, c)| match &c.parser {
+            ParserType::DeepLink(codec) => Some((v, codec)),
+            _ => None,
+        });
+
+    let (variant, codec) = match deep_links.next() {
+        Some(deep_link) => deep_link,
+        None => return Ok(None),
+    };
+    if let Some((other, _)) = deep_links.next() {
+        return Err(compile_error_at(
+            "only one variant can be marked with `deep_link`",
+            other.ident.span(),
+        ));
+    }
+
+    let ty = match variant.fields.iter().collect::<Vec<_>>()[..] {
+        [field] => option_inner(&field.ty).unwrap_or(&field.ty),
+        _ => {
+            return Err(compile_error_at(
+                "`deep_link` variant must have exactly 1 field",
+                variant.ident.span(),
+            ))
+        }
+    };
+    let encode = codec.encode();
+    let is_valid = impl_is_valid();
+    let example = format!(
+        "```ignore\nconst REFERRAL: &str = \
+         {type_name}::checked_deep_link_payload(\"ref_42\");\nlet link = \
+         {type_name}::deep_link(\"my_bot\", &REFERRAL.to_owned());\n```"
+    );
+
+    Ok(Some(quote! {
+        /// Returns a deep link to the bot `bot_username`, which makes it
+        /// receive `/start` with `payload`.
+        ///
+        /// Returns `None` if the encoded payload is longer than 64 characters
+        /// or contains characters other than `A-Z`, `a-z`, `0-9`, `_` and `-`,
+        /// since Telegram doesn't accept such payloads and the payload
+        /// (unlike a literal) can't be checked at compile time. Parsing
+        /// `/start` rejects such payloads too.
+        pub fn deep_link(bot_username: &str, payload: &#ty) -> Option<String> {
+            let payload: String = #encode;
+            let payload = payload.as_str();
+            let valid = #is_valid;
+
+            valid.then(|| {
+                format!("https://t.me/{}?start={}", bot_username.trim_start_matches('@'), payload)
+            })
+        }
+
+        /// Checks that `payload` is a valid deep link payload, panicking
+        /// otherwise.
+        ///
+        /// When used in a constant, an invalid payload is a compile-time
+        /// error:
+        ///
+        #[doc = #example]
+        pub const fn checked_deep_link_payload(payload: &str) -> &str {
+            let bytes = payload.as_bytes();
+            if bytes.len() > #MAX_PAYLOAD_LEN {
+                panic!("deep link payload is longer than 64 characters");
+            }
+
+            let mut i = 0;
+            while i < bytes.len() {
+                let c = bytes[i];
+                if !(c.is_ascii_alphanumeric() || c == b'_' || c == b'-') {
+                    panic!("deep link payload can only contain `A-Z`, `a-z`, `0-9`, `_` and `-`");
+                }
+                i += 1;
+            }
+
+            payload
+        }
+    }))
+}
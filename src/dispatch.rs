@@ -0,0 +1,93 @@
+use crate::{
+    command::Command, command_enum::CommandEnum, error::compile_error_at,
+    Result,
+};
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Fields, Type, Variant};
+
+/// Generates `dispatch` which calls handlers of commands with the context and
+/// the fields of the variant.
+///
+/// If dispatch is not exhaustive, variants without a handler are returned in
+/// `Err(_)`.
+pub(crate) fn impl_dispatch<'a>(
+    variants: impl Iterator<Item = &'a Variant>,
+    infos: &[Command],
+    global: &CommandEnum,
+    context: &Type,
+) -> Result<TokenStream> {
+    let output = match &global.handler_output {
+        Some(output) => quote! { #output },
+        None => quote! { () },
+    };
+    let exhaustive = global.handler_exhaustive;
+
+    let arms = variants
+        .zip(infos)
+        .map(|(variant, command)| {
+            let name = &variant.ident;
+            let handler = match &command.handler {
+                Some(handler) => handler,
+                None if exhaustive => {
+                    return Err(compile_error_at(
+                        "no `handler` is specified for the command, while \
+                         `handler_exhaustive` is set",
+                        name.span(),
+                    ))
+                }
+                None => {
+                    return Ok(
+                        quote! { this @ Self::#name { .. } => Err(this) },
+                    )
+                }
+            };
+
+            let (pattern, args) = destructure(&variant.fields);
+            let call = quote! { #handler(ctx, #(#args),*).await };
+            let call = match exhaustive {
+                true => call,
+                false => quote! { Ok(#call) },
+            };
+
+            Ok(quote! { Self::#name #pattern => #call })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let ret = match exhaustive {
+        true => output,
+        false => quote! { Result<#output, Self> },
+    };
+
+    Ok(quote! {
+        /// Calls the handler of this command with `ctx` and the command
+        /// arguments.
+        pub async fn dispatch(self, ctx: #context) -> #ret {
+            match self {
+                #( #arms, )*
+            }
+        }
+    })
+}
+
+/// Returns a pattern which destructures `fields` and the bindings it
+/// introduces.
+///
+/// Bindings are always named `argN`, so that fields can't shadow `ctx`.
+fn destructure(fields: &Fields) -> (TokenStream, Vec<syn::Ident>) {
+    let args = (0..fields.len())
+        .map(|i| format_ident!("arg{}", i))
+        .collect::<Vec<_>>();
+
+    let pattern = match fields {
+        Fields::Unit => quote! {},
+        Fields::Unnamed(_) => quote! { (#(#args),*) },
+        Fields::Named(fields) => {
+            let names = fields.named.iter().map(|f| &f.ident);
+            quote! { { #(#names: #args),* } }
+        }
+    };
+
+    (pattern, args)
+}
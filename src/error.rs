@@ -41,6 +41,14 @@ pub(crate) fn compile_error_at(msg: &str, sp: Span) -> Error {
     Error(ts)
 }
 
+impl Error {
+    /// Adds errors of `other` to this error, so that all of them are reported.
+    pub(crate) fn combine(mut self, Error(other): Error) -> Self {
+        self.0.extend(other);
+        self
+    }
+}
+
 impl From<Error> for proc_macro2::TokenStream {
     fn from(Error(e): Error) -> Self {
         e
@@ -1,24 +1,45 @@
-use quote::quote;
-use syn::{Fields, FieldsNamed, FieldsUnnamed, Type};
+use quote::{format_ident, quote, quote_spanned};
+use syn::{
+    punctuated::Punctuated, spanned::Spanned, Attribute, Field, Fields,
+    FieldsNamed, FieldsUnnamed, Token, Type,
+};
 
-use crate::{attr::AttrValue, error::Result};
+use crate::{
+    attr::{fold_attrs, Attr, AttrValue},
+    command_attr::is_command_attribute,
+    deep_link::DeepLinkCodec,
+    error::{compile_error_at, Result},
+    regex_parse::RegexParser,
+};
 
 #[derive(Clone)]
 pub(crate) enum ParserType {
     Default,
-    Split { separator: Option<String> },
+    Split { separator: Option<String>, collapse: bool },
     Custom(syn::Path),
+    DeepLink(DeepLinkCodec),
+    Regex(RegexParser),
 }
 
 impl ParserType {
     pub fn parse(value: AttrValue) -> Result<Self> {
+        // `regex(...)` is handled separately, since it has its own errors
+        if let AttrValue::Call(p, args, _) = &value {
+            if p.is_ident("regex") {
+                return RegexParser::parse(args.clone()).map(ParserType::Regex);
+            }
+        }
+
         value.expect(
-            r#""default", "split", or a path to a custom parser function"#,
+            r#""default", "split", `regex("...")`, or a path to a custom parser function"#,
             |v| match v {
                 AttrValue::Path(p) => Ok(ParserType::Custom(p)),
                 AttrValue::Lit(syn::Lit::Str(ref l)) => match &*l.value() {
                     "default" => Ok(ParserType::Default),
-                    "split" => Ok(ParserType::Split { separator: None }),
+                    "split" => Ok(ParserType::Split {
+                        separator: None,
+                        collapse: false,
+                    }),
                     _ => Err(v),
                 },
                 _ => Err(v),
@@ -31,61 +52,220 @@ pub(crate) fn impl_parse_args(
     fields: &Fields,
     self_variant: proc_macro2::TokenStream,
     parser: &ParserType,
-) -> proc_macro2::TokenStream {
-    match fields {
+) -> Result<proc_macro2::TokenStream> {
+    let res = match fields {
         Fields::Unit => self_variant,
         Fields::Unnamed(fields) => {
-            impl_parse_args_unnamed(fields, self_variant, parser)
+            impl_parse_args_unnamed(fields, self_variant, parser)?
         }
         Fields::Named(named) => {
-            impl_parse_args_named(named, self_variant, parser)
+            impl_parse_args_named(named, self_variant, parser)?
         }
-    }
+    };
+
+    Ok(res)
 }
 
 pub(crate) fn impl_parse_args_unnamed(
     data: &FieldsUnnamed,
     variant: proc_macro2::TokenStream,
     parser_type: &ParserType,
-) -> proc_macro2::TokenStream {
-    let get_arguments =
-        create_parser(parser_type, data.unnamed.iter().map(|f| &f.ty));
-    let iter = (0..data.unnamed.len()).map(syn::Index::from);
-    let mut initialization = quote! {};
-    for i in iter {
-        initialization.extend(quote! { arguments.#i, })
-    }
+) -> Result<proc_macro2::TokenStream> {
+    let (get_arguments, values) =
+        create_field_parser(parser_type, &data.unnamed)?;
     let res = quote! {
         {
             #get_arguments
-            #variant(#initialization)
+            #variant(#(#values),*)
         }
     };
-    res
+    Ok(res)
 }
 
 pub(crate) fn impl_parse_args_named(
     data: &FieldsNamed,
     variant: proc_macro2::TokenStream,
     parser_type: &ParserType,
-) -> proc_macro2::TokenStream {
-    let get_arguments =
-        create_parser(parser_type, data.named.iter().map(|f| &f.ty));
-    let i = (0..).map(syn::Index::from);
+) -> Result<proc_macro2::TokenStream> {
+    let (get_arguments, values) =
+        create_field_parser(parser_type, &data.named)?;
     let name = data.named.iter().map(|f| f.ident.as_ref().unwrap());
     let res = quote! {
         {
             #get_arguments
-            #variant { #(#name: arguments.#i),* }
+            #variant { #(#name: #values),* }
         }
     };
-    res
+    Ok(res)
 }
 
-fn create_parser<'a>(
+/// Creates code which parses all `fields` and returns it, along with
+/// expressions for the values of each field.
+///
+/// Fields marked with `#[command(from_mention)]` are taken from `mentions` and
+/// aren't passed to the parser.
+fn create_field_parser(
     parser_type: &ParserType,
-    mut types: impl ExactSizeIterator<Item = &'a Type>,
-) -> proc_macro2::TokenStream {
+    fields: &Punctuated<Field, Token![,]>,
+) -> Result<(proc_macro2::TokenStream, Vec<proc_macro2::TokenStream>)> {
+    let from_mention = fields
+        .iter()
+        .map(|f| FieldAttrs::from_attributes(&f.attrs).map(|a| a.from_mention))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mention_types = fields
+        .iter()
+        .zip(&from_mention)
+        .filter(|(_, &m)| m)
+        .map(|(f, _)| &f.ty)
+        .collect::<Vec<_>>();
+    let argument_fields = fields
+        .iter()
+        .zip(&from_mention)
+        .filter(|(_, &m)| !m)
+        .map(|(f, _)| f)
+        .collect::<Vec<_>>();
+
+    let (mut mention_i, mut argument_i) = (0usize, 0);
+    let values = from_mention
+        .iter()
+        .map(|&m| match m {
+            true => {
+                let ident = format_ident!("mention_{}", mention_i);
+                mention_i += 1;
+                quote! { #ident }
+            }
+            false => {
+                let i = syn::Index::from(argument_i);
+                argument_i += 1;
+                quote! { arguments.#i }
+            }
+        })
+        .collect();
+
+    let get_mentions = match mention_types.is_empty() {
+        true => quote! {},
+        false => get_mentions(
+            &mention_types,
+            crate::arguments::separator(parser_type).unwrap_or(" "),
+        ),
+    };
+    let get_arguments = match argument_fields.is_empty() {
+        true => quote! {},
+        false => create_parser(parser_type, &argument_fields)?,
+    };
+
+    Ok((quote! { #get_mentions #get_arguments }, values))
+}
+
+/// Creates code which converts first `types.len()` mentions from `mentions`
+/// into values of `types`, and removes them (along with whitespace and
+/// separators around them) from `args`, joining what is left with
+/// `separator`.
+///
+/// Whether a mention has a user (a `TextMention` entity) or just a username
+/// is only known at runtime, so `types` must implement both `From<User>` and
+/// `FromStr`. The conversions are spanned to the types, so that a missing
+/// implementation is reported at the field.
+fn get_mentions(types: &[&Type], separator: &str) -> proc_macro2::TokenStream {
+    let expected = types.len();
+    let idents = (0..expected).map(|i| format_ident!("mention_{}", i));
+    let i = 0..expected;
+    let from_user = types.iter().map(|ty| {
+        quote_spanned! { ty.span()=> <#ty as From<teloxide::types::User>>::from((*user).clone()) }
+    });
+    let from_username = types.iter().map(|ty| {
+        quote_spanned! { ty.span()=> <#ty as FromStr>::from_str(&args[range.clone()]) }
+    });
+
+    quote! {
+        if mentions.len() < #expected {
+            return Err(ParseError::TooFewArguments {
+                expected: #expected,
+                found: mentions.len(),
+                message: format!("Expected {} mentions, found {}", #expected, mentions.len()),
+            });
+        }
+        let used_mentions = &mentions[..#expected];
+
+        #(
+            let #idents = {
+                let (range, user) = &used_mentions[#i];
+                match user {
+                    Some(user) => #from_user,
+                    None => #from_username
+                        .map_err(|e| ParseError::IncorrectFormat(e.into()))?,
+                }
+            };
+        )*
+
+        let args = {
+            fn trim(piece: &str) -> &str {
+                piece.trim().trim_start_matches(#separator).trim_end_matches(#separator).trim()
+            }
+            let mut pieces = Vec::new();
+            let mut last = 0;
+            for (range, _) in used_mentions {
+                pieces.push(trim(&args[last..range.start]));
+                last = range.end;
+            }
+            pieces.push(trim(&args[last..]));
+            pieces.retain(|p| !p.is_empty());
+            pieces.join(#separator)
+        };
+    }
+}
+
+/// Attributes of a field of a variant.
+pub(crate) struct FieldAttrs {
+    /// Whether the field is taken from a mention, `#[command(from_mention)]`.
+    /// Its type must implement `From<User>` (for `TextMention` entities) and
+    /// `FromStr` (for usernames).
+    pub from_mention: bool,
+    /// Name of the argument in usage, `#[command(rename = "...")]`.
+    pub rename: Option<String>,
+}
+
+impl FieldAttrs {
+    pub fn from_attributes(attrs: &[Attribute]) -> Result<Self> {
+        fold_attrs(
+            attrs,
+            is_command_attribute,
+            Ok,
+            Self { from_mention: false, rename: None },
+            |mut this, Attr { key, value }| {
+                let sp = key.span();
+                match &*key.to_string() {
+                    "from_mention" if !this.from_mention => {
+                        value.expect_none()?;
+                        this.from_mention = true;
+                    }
+                    "rename" if this.rename.is_none() => {
+                        this.rename = Some(value.expect_string()?);
+                    }
+                    "from_mention" | "rename" => {
+                        return Err(compile_error_at("duplicate attribute", sp))
+                    }
+                    _ => {
+                        return Err(compile_error_at(
+                            "unexpected attribute name (expected \
+                             `from_mention` or `rename`)",
+                            sp,
+                        ))
+                    }
+                }
+
+                Ok(this)
+            },
+        )
+    }
+}
+
+fn create_parser(
+    parser_type: &ParserType,
+    fields: &[&Field],
+) -> Result<proc_macro2::TokenStream> {
+    let mut types = fields.iter().map(|f| &f.ty);
     let function_to_parse = match parser_type {
         ParserType::Default => match types.len() {
             1 => {
@@ -105,20 +285,74 @@ fn create_parser<'a>(
                 quote! { compile_error!("Default parser works only with exactly 1 field") }
             }
         },
-        ParserType::Split { separator } => parser_with_separator(
+        ParserType::Split { separator, collapse } => parser_with_separator(
             &separator.clone().unwrap_or_else(|| " ".to_owned()),
+            *collapse,
             types,
         ),
         ParserType::Custom(path) => quote! { #path },
+        ParserType::DeepLink(codec) => match types.len() {
+            1 => {
+                let ty = types.next().unwrap();
+                // `Option<_>` payload is `None` for a plain `/start`
+                let value = match option_inner(ty) {
+                    Some(inner) => {
+                        let decode = codec.decode(inner);
+                        quote! {
+                            match payload.is_empty() {
+                                true => None,
+                                false => Some(#decode),
+                            }
+                        }
+                    }
+                    None => codec.decode(ty),
+                };
+                quote! {
+                    (
+                        |s: String| {
+                            let payload: &str = &s;
+                            Ok((#value,))
+                        }
+                    )
+                }
+            }
+            _ => {
+                quote! { compile_error!("Deep link parser works only with exactly 1 field") }
+            }
+        },
+        ParserType::Regex(regex) => regex.impl_parse(fields)?,
     };
 
-    quote! {
+    Ok(quote! {
         let arguments = #function_to_parse(args)?;
+    })
+}
+
+/// Returns `T` if `ty` is `Option<T>`.
+pub(crate) fn option_inner(ty: &Type) -> Option<&Type> {
+    let path = match ty {
+        Type::Path(ty) if ty.qself.is_none() => &ty.path,
+        _ => return None,
+    };
+    let segment = path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) if args.args.len() == 1 => {
+            match &args.args[0] {
+                syn::GenericArgument::Type(inner) => Some(inner),
+                _ => None,
+            }
+        }
+        _ => None,
     }
 }
 
 fn parser_with_separator<'a>(
     separator: &str,
+    collapse: bool,
     types: impl ExactSizeIterator<Item = &'a Type>,
 ) -> proc_macro2::TokenStream {
     let expected = types.len();
@@ -141,10 +375,17 @@ fn parser_with_separator<'a>(
         }
     };
 
+    // Repeated separators produce empty strings in between, which are
+    // skipped, if separators are collapsed.
+    let skip_empty = match collapse {
+        true => quote! { .filter(|s| !s.is_empty()) },
+        false => quote! {},
+    };
+
     let res = quote! {
         (
             |s: String| {
-                let mut splitted = s.split(#separator);
+                let mut splitted = s.split(#separator)#skip_empty;
 
                 let res = #res;
 
@@ -1,13 +1,24 @@
 extern crate proc_macro;
 
+mod accessors;
+mod arguments;
 mod attr;
 mod bot_commands;
+mod categories;
 mod command;
 mod command_attr;
 mod command_enum;
+mod command_kind;
+mod deep_link;
+mod dispatch;
 mod error;
 mod fields_parse;
+mod parse_options;
+mod pattern;
+mod regex_parse;
 mod rename_rules;
+mod suggestions;
+mod trigger;
 mod unzip;
 
 pub(crate) use error::{compile_error, Result};
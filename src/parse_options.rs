@@ -0,0 +1,72 @@
+use crate::command_enum::CommandEnum;
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Ident, Visibility};
+
+/// Generates a struct `options` with runtime options of parsing, together
+/// with `parse_with_options` for the command enum.
+pub(crate) fn impl_parse_options(
+    type_name: &Ident,
+    vis: &Visibility,
+    options: &Ident,
+    global: &CommandEnum,
+) -> TokenStream {
+    let case_insensitive = global.case_insensitive.is_some();
+    let options_doc = format!(
+        "Runtime options of [`{type_name}::parse_with_options`].\n\nThe \
+         default options are the ones used by `parse`, except that there are \
+         no `bot_usernames` (which `parse` takes as `bot_name`), so the \
+         username of the bot has to be set for commands mentioning it."
+    );
+
+    quote! {
+        #[doc = #options_doc]
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        #vis struct #options<'a> {
+            /// Prefixes of all commands, replacing their own prefixes, if
+            /// set. If several prefixes match, the longest one is used.
+            pub prefixes: Option<&'a [&'a str]>,
+            /// Usernames of the bot, which commands can be addressed to via
+            /// `/command@username`. Empty by default, so that any mention is
+            /// treated as a mention of another bot.
+            pub bot_usernames: &'a [&'a str],
+            /// Whether command names are matched regardless of case.
+            pub case_insensitive: bool,
+        }
+
+        impl Default for #options<'_> {
+            fn default() -> Self {
+                Self {
+                    prefixes: None,
+                    bot_usernames: &[],
+                    case_insensitive: #case_insensitive,
+                }
+            }
+        }
+
+        impl #type_name {
+            /// Parses a command with the runtime `options`, in the same way
+            /// as `parse` with `options.bot_usernames` in place of its
+            /// `bot_name`.
+            pub fn parse_with_options(
+                s: &str,
+                options: &#options<'_>,
+            ) -> Result<Self, teloxide::utils::command::ParseError> {
+                Self::__parse_text(
+                    s,
+                    options.bot_usernames,
+                    options.prefixes,
+                    options.case_insensitive,
+                )?
+                .ok_or_else(|| {
+                    Self::__not_addressed(
+                        s,
+                        options.prefixes,
+                        options.case_insensitive,
+                    )
+                })
+            }
+        }
+    }
+}
@@ -0,0 +1,262 @@
+use crate::{command::Command, error::compile_error_at, Result};
+
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{Field, Fields, Ident, Variant};
+
+/// A command name with placeholders for fields, e.g. `view_{id}`, see
+/// `#[command(pattern = "...")]`.
+pub(crate) struct Pattern {
+    /// The pattern as written by the user.
+    pub source: String,
+    /// Literal parts of the pattern, there is always one more literal than
+    /// placeholders (literals at the start and at the end can be empty).
+    literals: Vec<String>,
+    /// Names of the fields (or indices of unnamed fields) in placeholders.
+    placeholders: Vec<String>,
+    span: Span,
+}
+
+impl Pattern {
+    pub fn parse(source: String, span: Span) -> Result<Self> {
+        let mut literals = vec![String::new()];
+        let mut placeholders = Vec::new();
+
+        let mut rest = &*source;
+        while let Some(start) = rest.find(['{', '}']) {
+            literals.last_mut().unwrap().push_str(&rest[..start]);
+
+            let end = match rest[start..].starts_with('{') {
+                true => rest[start..].find('}').map(|end| start + end),
+                false => None,
+            };
+            let end = end.ok_or_else(|| {
+                compile_error_at("unmatched brace in `pattern`", span)
+            })?;
+
+            let placeholder = &rest[start + 1..end];
+            if placeholder.is_empty() {
+                return Err(compile_error_at(
+                    "empty placeholder in `pattern`, expected a field name or \
+                     index",
+                    span,
+                ));
+            }
+            if literals.last().unwrap().is_empty() && !placeholders.is_empty() {
+                return Err(compile_error_at(
+                    "placeholders in `pattern` must be separated by some text",
+                    span,
+                ));
+            }
+
+            placeholders.push(placeholder.to_owned());
+            literals.push(String::new());
+            rest = &rest[end + 1..];
+        }
+        literals.last_mut().unwrap().push_str(rest);
+
+        Ok(Self { source, literals, placeholders, span })
+    }
+
+    /// Returns code which tries to match `name` (a command without `prefix`)
+    /// against the pattern, if `prefix_check` passes, returning the
+    /// initialized variant on success.
+    pub fn impl_match(
+        &self,
+        prefix_check: TokenStream,
+        variant: &Variant,
+    ) -> Result<TokenStream> {
+        let fields = self.placeholder_fields(variant)?;
+        let (bindings, pattern) = self.bindings(variant, &fields);
+        let literals = &self.literals;
+        let types = fields.iter().map(|(_, f)| &f.ty);
+        let i = 0..fields.len();
+
+        Ok(quote! {
+            if let Some(captures) = #prefix_check.then(|| Self::__match_pattern(name, &[#(#literals),*])).flatten() {
+                if !args.is_empty() {
+                    return Err(ParseError::TooManyArguments {
+                        expected: 0,
+                        found: 1,
+                        message: format!("Excess argument: {}", args),
+                    });
+                }
+
+                #(
+                    let #bindings = <#types>::from_str(captures[#i])
+                        .map_err(|e| ParseError::IncorrectFormat(e.into()))?;
+                )*
+
+                return Ok(Some(#pattern));
+            }
+        })
+    }
+
+    /// Returns a `match` arm which formats the variant according to the
+    /// pattern (with `prefix`).
+    pub fn impl_format(
+        &self,
+        prefix: &str,
+        variant: &Variant,
+    ) -> Result<TokenStream> {
+        let fields = self.placeholder_fields(variant)?;
+        let (bindings, pattern) = self.bindings(variant, &fields);
+        let format = format!("{prefix}{}", self.literals.join("{}"));
+
+        Ok(quote! { #pattern => Some(format!(#format, #(#bindings),*)) })
+    }
+
+    /// Returns bindings for placeholders (in the order of placeholders) and a
+    /// pattern which binds them, e.g. `Self::View { id: field_0 }`.
+    fn bindings(
+        &self,
+        variant: &Variant,
+        fields: &[(usize, &Field)],
+    ) -> (Vec<Ident>, TokenStream) {
+        let name = &variant.ident;
+        let bindings = (0..fields.len())
+            .map(|i| format_ident!("field_{}", i))
+            .collect::<Vec<_>>();
+
+        let pattern = match &variant.fields {
+            Fields::Unit => quote! { Self::#name },
+            Fields::Unnamed(_) => {
+                let mut by_index =
+                    fields.iter().zip(&bindings).collect::<Vec<_>>();
+                by_index.sort_by_key(|((i, _), _)| *i);
+                let bindings = by_index.into_iter().map(|(_, b)| b);
+                quote! { Self::#name(#(#bindings),*) }
+            }
+            Fields::Named(_) => {
+                let names = fields.iter().map(|(_, f)| &f.ident);
+                quote! { Self::#name { #(#names: #bindings),* } }
+            }
+        };
+
+        (bindings, pattern)
+    }
+
+    /// Returns fields (with their indices) named by placeholders, checking
+    /// that each field is named exactly once.
+    fn placeholder_fields<'a>(
+        &self,
+        variant: &'a Variant,
+    ) -> Result<Vec<(usize, &'a Field)>> {
+        let member = |i: usize, f: &Field| match &f.ident {
+            Some(ident) => ident.to_string(),
+            None => i.to_string(),
+        };
+
+        let fields = self
+            .placeholders
+            .iter()
+            .enumerate()
+            .map(|(i, placeholder)| {
+                if self.placeholders[..i].contains(placeholder) {
+                    return Err(compile_error_at(
+                        &format!(
+                            "field `{placeholder}` is used in `pattern` twice"
+                        ),
+                        self.span,
+                    ));
+                }
+
+                variant
+                    .fields
+                    .iter()
+                    .enumerate()
+                    .find(|&(i, f)| member(i, f) == *placeholder)
+                    .ok_or_else(|| {
+                        compile_error_at(
+                            &format!(
+                                "no field `{placeholder}` to fill from \
+                                 `pattern`"
+                            ),
+                            self.span,
+                        )
+                    })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        if let Some((i, f)) = variant
+            .fields
+            .iter()
+            .enumerate()
+            .find(|&(i, f)| !self.placeholders.contains(&member(i, f)))
+        {
+            return Err(compile_error_at(
+                &format!("field `{}` is not used in `pattern`", member(i, f)),
+                self.span,
+            ));
+        }
+
+        Ok(fields)
+    }
+}
+
+/// Generates a helper which matches a command name against literals of a
+/// pattern, returning the (non-empty) text between them.
+pub(crate) fn impl_match_pattern() -> TokenStream {
+    quote! {
+        fn __match_pattern<'a>(name: &'a str, literals: &[&str]) -> Option<Vec<&'a str>> {
+            let (first, literals) = literals.split_first()?;
+            let mut rest = name.strip_prefix(first)?;
+            let mut captures = Vec::with_capacity(literals.len());
+
+            for (i, literal) in literals.iter().enumerate() {
+                let end = match i + 1 == literals.len() {
+                    true => rest.strip_suffix(literal)?.len(),
+                    // Captures are non-empty, so search from the 2nd char
+                    false => rest.char_indices().nth(1).and_then(|(start, _)| {
+                        rest[start..].find(literal).map(|end| start + end)
+                    })?,
+                };
+                if end == 0 {
+                    return None;
+                }
+
+                captures.push(&rest[..end]);
+                rest = &rest[end + literal.len()..];
+            }
+
+            rest.is_empty().then_some(captures)
+        }
+    }
+}
+
+/// Generates `format_pattern`, which formats commands with a `pattern` back
+/// into their names.
+pub(crate) fn impl_format_pattern<'a>(
+    variants: impl Iterator<Item = &'a Variant>,
+    infos: &[Command],
+) -> Result<Option<TokenStream>> {
+    let mut arms = Vec::new();
+    let mut exhaustive = true;
+    for (variant, command) in variants.zip(infos) {
+        match &command.pattern {
+            Some(pattern) => {
+                arms.push(pattern.impl_format(&command.prefix, variant)?)
+            }
+            None => exhaustive = false,
+        }
+    }
+
+    if arms.is_empty() {
+        return Ok(None);
+    }
+    let other = match exhaustive {
+        true => quote! {},
+        false => quote! { _ => None, },
+    };
+
+    Ok(Some(quote! {
+        /// Formats this command as a command name according to its
+        /// `pattern`, or returns `None` if it doesn't have one.
+        pub fn format_pattern(&self) -> Option<String> {
+            match self {
+                #(#arms,)*
+                #other
+            }
+        }
+    }))
+}
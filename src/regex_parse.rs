@@ -0,0 +1,131 @@
+use crate::{error::compile_error_at, fields_parse::option_inner, Result};
+
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, quote_spanned};
+use syn::{Field, LitStr};
+
+/// Parser which takes fields from named capture groups of a regex, see
+/// `#[command(parse_with = regex("..."))]`.
+///
+/// The generated code uses the `regex` crate, which users have to add as a
+/// dependency of their own: it's not re-exported by `teloxide`. The path to
+/// it is spanned to the regex, so that a missing dependency is reported at
+/// the attribute.
+#[derive(Clone)]
+pub(crate) struct RegexParser {
+    /// The regex as written by the user.
+    source: String,
+    /// Names of capture groups of the regex.
+    names: Vec<String>,
+    span: Span,
+}
+
+impl RegexParser {
+    pub fn parse(args: TokenStream) -> Result<Self> {
+        let lit = syn::parse2::<LitStr>(args)?;
+        let source = lit.value();
+
+        let regex = regex::Regex::new(&source).map_err(|e| {
+            compile_error_at(&format!("invalid regex: {e}"), lit.span())
+        })?;
+        let names =
+            regex.capture_names().flatten().map(<_>::to_owned).collect();
+
+        Ok(Self { source, names, span: lit.span() })
+    }
+
+    /// Returns a parser which matches arguments against the regex and parses
+    /// `fields` from the capture groups with the same names.
+    ///
+    /// `Option<_>` fields are `None` if their groups didn't participate in the
+    /// match.
+    pub fn impl_parse(&self, fields: &[&Field]) -> Result<TokenStream> {
+        let names = fields
+            .iter()
+            .map(|f| {
+                f.ident.as_ref().map(|ident| ident.to_string()).ok_or_else(
+                    || {
+                        compile_error_at(
+                            "regex parser can only be used with named fields",
+                            self.span,
+                        )
+                    },
+                )
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        if let Some(name) = names.iter().find(|&n| !self.names.contains(n)) {
+            return Err(compile_error_at(
+                &format!("no capture group `{name}` in the regex"),
+                self.span,
+            ));
+        }
+        if let Some(name) = self.names.iter().find(|&n| !names.contains(n)) {
+            return Err(compile_error_at(
+                &format!("no field `{name}` for the capture group"),
+                self.span,
+            ));
+        }
+
+        let values = fields.iter().zip(&names).map(|(field, name)| {
+            match option_inner(&field.ty) {
+                Some(inner) => quote! {
+                    match captures.name(#name) {
+                        Some(m) => Some(
+                            <#inner>::from_str(m.as_str())
+                                .map_err(|e| ParseError::IncorrectFormat(e.into()))?
+                        ),
+                        None => None,
+                    }
+                },
+                None => {
+                    let ty = &field.ty;
+                    let missing = format!("Missing argument `{name}`");
+                    quote! {
+                        <#ty>::from_str(
+                            captures
+                                .name(#name)
+                                .ok_or_else(|| ParseError::IncorrectFormat(#missing.into()))?
+                                .as_str()
+                        )
+                        .map_err(|e| ParseError::IncorrectFormat(e.into()))?
+                    }
+                }
+            }
+        });
+
+        let regex = anchored(&self.source);
+        let mismatch = format!("Arguments don't match `{}`", self.source);
+
+        let regex_type = quote_spanned! { self.span=> ::regex::Regex };
+
+        Ok(quote! {
+            (
+                |s: String| {
+                    // A `Mutex` (unlike `OnceLock`) doesn't require Rust 1.70
+                    static REGEX: std::sync::Mutex<Option<#regex_type>> =
+                        std::sync::Mutex::new(None);
+
+                    fn new_regex() -> #regex_type {
+                        // Unwrap: the regex is checked at compile time
+                        #regex_type::new(#regex).unwrap()
+                    }
+
+                    let captures = REGEX
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .get_or_insert_with(new_regex)
+                        .captures(&s)
+                        .ok_or_else(|| ParseError::IncorrectFormat(#mismatch.into()))?;
+
+                    Ok((#(#values,)*))
+                }
+            )
+        })
+    }
+}
+
+/// Makes the regex match the whole arguments.
+fn anchored(source: &str) -> String {
+    format!("^(?:{source})$")
+}
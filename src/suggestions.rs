@@ -0,0 +1,84 @@
+use crate::command::Command;
+
+use proc_macro2::TokenStream;
+use quote::quote;
+
+/// Generates `suggestions`, which finds known commands similar to an unknown
+/// one.
+pub(crate) fn impl_suggestions(infos: &[Command]) -> TokenStream {
+    // Each command with all of its prefixes
+    let commands = infos
+        .iter()
+        .filter(|c| {
+            !c.is_hidden() && c.pattern.is_none() && c.trigger.is_none()
+        })
+        .map(|c| {
+            let names = c.all_prefixed_commands();
+            quote! { &[#(#names),*] }
+        });
+
+    quote! {
+         /// Returns known commands similar to the `unknown` one (e.g. from
+         /// [`ParseError::UnknownCommand`]), closest first, to suggest them
+         /// to the user.
+         ///
+         /// Commands are compared case-insensitively, allowing about one
+         /// typo per three characters.
+         ///
+         /// [`ParseError::UnknownCommand`]: teloxide::utils::command::ParseError::UnknownCommand
+         pub fn suggestions(unknown: &str) -> Vec<&'static str> {
+              let commands: &[&[&'static str]] = &[#(#commands),*];
+              let unknown = unknown.to_lowercase().chars().collect::<Vec<_>>();
+
+              // Each command is suggested once, with the closest prefix
+              let mut suggestions = commands
+                  .iter()
+                  .filter_map(|names| {
+                      names
+                          .iter()
+                          .map(|&name| {
+                              let known = name.to_lowercase().chars().collect::<Vec<_>>();
+                              let max_distance = (known.len() / 3).max(1);
+                              (Self::__edit_distance(&unknown, &known), max_distance, name)
+                          })
+                          .min_by_key(|&(distance, ..)| distance)
+                          .filter(|&(distance, max_distance, _)| distance <= max_distance)
+                          .map(|(distance, _, name)| (distance, name))
+                  })
+                  .collect::<Vec<_>>();
+              // Stable, so that commands at the same distance are in the
+              // declaration order
+              suggestions.sort_by_key(|&(distance, _)| distance);
+
+              suggestions.into_iter().map(|(_, name)| name).collect()
+         }
+
+         /// Returns the edit distance between `a` and `b`, where an edit is
+         /// an insertion, a deletion, a substitution or a transposition of
+         /// adjacent characters.
+         fn __edit_distance(a: &[char], b: &[char]) -> usize {
+              // `d[i][j]` is the distance between `a[..i]` and `b[..j]`
+              let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+              for (i, row) in d.iter_mut().enumerate() {
+                  row[0] = i;
+              }
+              for (j, cell) in d[0].iter_mut().enumerate() {
+                  *cell = j;
+              }
+
+              for i in 1..=a.len() {
+                  for j in 1..=b.len() {
+                      let cost = usize::from(a[i - 1] != b[j - 1]);
+                      d[i][j] = (d[i - 1][j] + 1)
+                          .min(d[i][j - 1] + 1)
+                          .min(d[i - 1][j - 1] + cost);
+                      if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                          d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+                      }
+                  }
+              }
+
+              d[a.len()][b.len()]
+         }
+    }
+}
@@ -0,0 +1,98 @@
+use crate::{
+    command::Command,
+    command_enum::{CaseFolding, CommandEnum},
+    error::compile_error,
+    Result,
+};
+
+use proc_macro2::TokenStream;
+use quote::quote;
+
+/// A keyword which triggers a command without a prefix, see
+/// `#[command(trigger = "...")]`.
+pub(crate) struct Trigger {
+    pub matching: TriggerMatch,
+    pub case_insensitive: bool,
+}
+
+/// What part of the text is matched against a trigger keyword.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum TriggerMatch {
+    /// The first word, the rest of the text are the arguments.
+    FirstWord,
+    /// The whole text, there are no arguments.
+    WholeMessage,
+}
+
+impl TriggerMatch {
+    pub fn parse(matching: &str) -> Result<Self> {
+        match matching {
+            "first_word" => Ok(Self::FirstWord),
+            "whole_message" => Ok(Self::WholeMessage),
+            invalid => Err(compile_error(format!(
+                "invalid `trigger_match` value `{invalid}` (supported values: \
+                 `first_word` and `whole_message`)"
+            ))),
+        }
+    }
+}
+
+/// Generates `__parse_trigger`, which parses commands triggered by keywords.
+pub(crate) fn impl_parse_trigger(
+    infos: &[Command],
+    variants_initialization: &[TokenStream],
+    global: &CommandEnum,
+) -> TokenStream {
+    let folding = global.case_insensitive.unwrap_or(CaseFolding::Ascii);
+    let triggers = infos.iter().zip(variants_initialization).filter_map(
+        |(command, init)| {
+            let trigger = command.trigger.as_ref()?;
+            let text = match trigger.matching {
+                TriggerMatch::FirstWord => quote! { word },
+                TriggerMatch::WholeMessage => quote! { s },
+            };
+            let keyword = &command.name;
+            let matches = match trigger.case_insensitive {
+                true => folding.impl_eq(text, keyword),
+                false => quote! { #text == #keyword },
+            };
+            let args = match trigger.matching {
+                TriggerMatch::FirstWord => quote! {
+                    let args = args.to_owned();
+                },
+                TriggerMatch::WholeMessage => quote! {
+                    let args = String::new();
+                    let mentions = &mentions[..0];
+                },
+            };
+
+            Some(quote! {
+                if #matches {
+                    #args
+                    return Ok(Some(#init));
+                }
+            })
+        },
+    );
+
+    quote! {
+         /// Parses a command triggered by a keyword, returning `Ok(None)` if
+         /// `s` doesn't match any of them.
+         #[allow(unused_variables)]
+         fn __parse_trigger(s: &str) -> Result<Option<Self>, teloxide::utils::command::ParseError> {
+              use std::str::FromStr;
+              use teloxide::utils::command::ParseError;
+
+              let s = s.trim();
+              let (word, args) = match s.find(char::is_whitespace) {
+                  Some(i) => (&s[..i], s[i..].trim_start()),
+                  None => (s, ""),
+              };
+              let mentions = Self::__text_mentions(args);
+
+              #(#triggers)*
+
+              Ok(None)
+         }
+    }
+}
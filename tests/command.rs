@@ -217,6 +217,7 @@ fn parse_named_fields() {
 }
 
 #[test]
+#[allow(deprecated)]
 fn descriptions_off() {
     #[derive(BotCommands, Debug, PartialEq)]
     #[command(rename_rule = "lowercase")]
@@ -296,3 +297,1334 @@ fn rename_rules() {
         DefaultCommands::descriptions().to_string()
     );
 }
+
+#[test]
+fn command_kind() {
+    #[derive(BotCommands, Debug, PartialEq)]
+    #[command(rename_rule = "lowercase", kind = DefaultCommandsKind)]
+    enum DefaultCommands {
+        #[command(description = "start the bot")]
+        Start(String),
+        #[command(prefix = "!")]
+        Help,
+        #[command(parse_with = "split")]
+        Ban { id: u64, reason: String },
+    }
+
+    use DefaultCommandsKind as K;
+
+    assert_eq!(K::ALL, &[K::Start, K::Help, K::Ban]);
+    assert_eq!(
+        DefaultCommands::parse("/start x", "").unwrap().kind(),
+        K::Start
+    );
+    assert_eq!(DefaultCommands::Help.kind(), K::Help);
+    assert_eq!(
+        DefaultCommands::Ban { id: 1, reason: "spam".to_owned() }.kind(),
+        K::Ban
+    );
+
+    assert_eq!(K::Start.command_name(), "start");
+    assert_eq!(K::Help.prefix(), "!");
+    assert_eq!(K::Help.prefixed_command(), "!help");
+    assert_eq!(K::Start.description(), Some("start the bot"));
+    assert_eq!(K::Ban.description(), None);
+}
+
+#[test]
+fn per_value_accessors() {
+    #[derive(BotCommands, Debug, PartialEq)]
+    #[command(rename_rule = "snake_case")]
+    enum DefaultCommands {
+        #[command(description = "ban a user", parse_with = "split")]
+        BanUser { id: u64, reason: String },
+        #[command(prefix = "!", hide)]
+        Help,
+    }
+
+    let ban = DefaultCommands::parse("/ban_user 10 spam", "").unwrap();
+    assert_eq!(ban.command_name(), "ban_user");
+    assert_eq!(ban.prefix(), "/");
+    assert_eq!(ban.prefixed_command(), "/ban_user");
+    assert_eq!(ban.description(), Some("ban a user"));
+
+    assert_eq!(DefaultCommands::Help.prefixed_command(), "!help");
+    assert_eq!(DefaultCommands::Help.description(), None);
+}
+
+#[test]
+fn user_defined_meta() {
+    #[derive(Debug, Default, PartialEq)]
+    enum Role {
+        #[default]
+        User,
+        Admin,
+    }
+
+    #[derive(Debug, Default, PartialEq)]
+    struct CommandMeta {
+        role: Role,
+        cooldown_secs: u32,
+        audit: bool,
+    }
+
+    #[derive(BotCommands, Debug, PartialEq)]
+    #[command(
+        rename_rule = "lowercase",
+        meta_type = CommandMeta,
+        kind = DefaultCommandsKind
+    )]
+    enum DefaultCommands {
+        #[command(meta(role = Role::Admin, cooldown_secs = 10, audit = true))]
+        Ban(u64),
+        #[command(meta(cooldown_secs = 60 * 2))]
+        Stats,
+        Help,
+    }
+
+    assert_eq!(
+        DefaultCommands::parse("/ban 1", "").unwrap().meta(),
+        CommandMeta { role: Role::Admin, cooldown_secs: 10, audit: true }
+    );
+    assert_eq!(
+        DefaultCommands::Stats.kind().meta(),
+        CommandMeta { role: Role::User, cooldown_secs: 120, audit: false }
+    );
+    assert_eq!(DefaultCommands::Help.meta(), CommandMeta::default());
+}
+
+#[test]
+fn permission_levels() {
+    #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+    enum Perm {
+        User,
+        Moderator,
+        Admin,
+    }
+
+    #[derive(BotCommands, Debug, PartialEq)]
+    #[command(rename_rule = "lowercase", permission_type = Perm)]
+    enum DefaultCommands {
+        #[command(description = "show help")]
+        Help,
+        #[command(description = "mute a user", permission = Perm::Moderator)]
+        Mute,
+        #[command(description = "ban a user", permission = Perm::Admin)]
+        Ban,
+    }
+
+    assert_eq!(DefaultCommands::Help.required_permission(), None);
+    assert_eq!(
+        DefaultCommands::parse("/ban", "").unwrap().required_permission(),
+        Some(Perm::Admin)
+    );
+
+    assert_eq!(
+        DefaultCommands::descriptions_for(Perm::User).to_string(),
+        "/help — show help"
+    );
+    assert_eq!(
+        DefaultCommands::descriptions_for(Perm::Moderator).to_string(),
+        "/help — show help\n/mute — mute a user"
+    );
+    assert_eq!(
+        DefaultCommands::descriptions_for(Perm::Admin).to_string(),
+        DefaultCommands::descriptions().to_string()
+    );
+    assert_eq!(
+        DefaultCommands::descriptions_for(Perm::User).to_string(),
+        "/help — show help"
+    );
+
+    let commands = DefaultCommands::bot_commands_for(Perm::Moderator);
+    assert_eq!(
+        commands.iter().map(|c| &*c.command).collect::<Vec<_>>(),
+        ["/help", "/mute"]
+    );
+}
+
+#[test]
+fn dispatch_to_handlers() {
+    use std::{
+        future::Future,
+        pin::pin,
+        task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+    };
+
+    // Handlers below never actually suspend, so polling once is enough.
+    fn now<F: Future>(fut: F) -> F::Output {
+        const VTABLE: RawWakerVTable = RawWakerVTable::new(
+            |_| RawWaker::new(std::ptr::null(), &VTABLE),
+            |_| {},
+            |_| {},
+            |_| {},
+        );
+        let waker = unsafe {
+            Waker::from_raw(RawWaker::new(std::ptr::null(), &VTABLE))
+        };
+
+        match pin!(fut).poll(&mut Context::from_waker(&waker)) {
+            Poll::Ready(res) => res,
+            Poll::Pending => panic!("handler has suspended"),
+        }
+    }
+
+    mod handlers {
+        pub struct Ctx {
+            pub chat: &'static str,
+        }
+
+        pub async fn ban(ctx: Ctx, id: u64, reason: String) -> String {
+            format!("{}: banned {id} for {reason}", ctx.chat)
+        }
+    }
+
+    use handlers::Ctx;
+
+    async fn help(ctx: Ctx) -> String {
+        format!("{}: help", ctx.chat)
+    }
+
+    async fn echo(ctx: Ctx, text: String) -> String {
+        format!("{}: {text}", ctx.chat)
+    }
+
+    #[derive(BotCommands, Debug, PartialEq)]
+    #[command(
+        rename_rule = "lowercase",
+        handler_context = Ctx,
+        handler_output = String,
+        handler_exhaustive
+    )]
+    enum DefaultCommands {
+        #[command(handler = help)]
+        Help,
+        #[command(handler = echo)]
+        Echo(String),
+        #[command(handler = handlers::ban, parse_with = "split")]
+        Ban { id: u64, reason: String },
+    }
+
+    #[derive(BotCommands, Debug, PartialEq)]
+    #[command(rename_rule = "lowercase", handler_context = Ctx)]
+    enum PartialCommands {
+        #[command(handler = partial_help)]
+        Help,
+        Start,
+    }
+
+    async fn partial_help(_: Ctx) {}
+
+    #[derive(BotCommands, Debug, PartialEq)]
+    #[command(
+        rename_rule = "lowercase",
+        handler_context = (Ctx, u32),
+        handler_output = &'static str,
+        handler_exhaustive
+    )]
+    enum TupleCommands {
+        #[command(handler = tuple_help)]
+        Help,
+    }
+
+    async fn tuple_help((_, n): (Ctx, u32)) -> &'static str {
+        match n {
+            1 => "one",
+            _ => "many",
+        }
+    }
+
+    let ctx = || Ctx { chat: "chat" };
+    let dispatch =
+        |s| now(DefaultCommands::parse(s, "").unwrap().dispatch(ctx()));
+
+    assert_eq!(dispatch("/help"), "chat: help");
+    assert_eq!(dispatch("/echo hi"), "chat: hi");
+    assert_eq!(dispatch("/ban 1 spam"), "chat: banned 1 for spam");
+
+    assert_eq!(now(PartialCommands::Help.dispatch(ctx())), Ok(()));
+    assert_eq!(now(TupleCommands::Help.dispatch((ctx(), 1))), "one");
+    assert_eq!(
+        now(PartialCommands::parse("/start", "").unwrap().dispatch(ctx())),
+        Err(PartialCommands::Start)
+    );
+}
+
+#[test]
+fn mention_modes() {
+    use teloxide::utils::command::ParseError;
+
+    #[derive(BotCommands, Debug, PartialEq)]
+    #[command(rename_rule = "lowercase")]
+    enum OptionalCommands {
+        Help,
+    }
+
+    #[derive(BotCommands, Debug, PartialEq)]
+    #[command(rename_rule = "lowercase", mention = "ignore_others")]
+    enum IgnoreOthersCommands {
+        Help,
+    }
+
+    #[derive(BotCommands, Debug, PartialEq)]
+    #[command(rename_rule = "lowercase", mention = "required")]
+    enum RequiredCommands {
+        Help,
+    }
+
+    assert!(matches!(
+        OptionalCommands::parse_addressed("/help@OtherBot", "MyBot"),
+        Err(ParseError::WrongBotName(name)) if name == "OtherBot"
+    ));
+    assert_eq!(
+        OptionalCommands::parse_addressed("/help", "MyBot").unwrap(),
+        Some(OptionalCommands::Help)
+    );
+
+    assert_eq!(
+        IgnoreOthersCommands::parse_addressed("/help@OtherBot", "MyBot")
+            .unwrap(),
+        None
+    );
+    assert_eq!(
+        IgnoreOthersCommands::parse_addressed("/help", "MyBot").unwrap(),
+        Some(IgnoreOthersCommands::Help)
+    );
+    assert!(matches!(
+        IgnoreOthersCommands::parse("/help@OtherBot", "MyBot"),
+        Err(ParseError::WrongBotName(name)) if name == "OtherBot"
+    ));
+
+    assert_eq!(
+        RequiredCommands::parse_addressed("/help", "MyBot").unwrap(),
+        None
+    );
+    assert_eq!(
+        RequiredCommands::parse_addressed("/help@OtherBot", "MyBot").unwrap(),
+        None
+    );
+    assert_eq!(
+        RequiredCommands::parse("/help@mybot", "MyBot").unwrap(),
+        RequiredCommands::Help
+    );
+    // `parse` can't return `None`, so a missing mention is an error
+    match RequiredCommands::parse("/help", "MyBot") {
+        Err(ParseError::Custom(e)) => {
+            assert_eq!(e.to_string(), "Command /help must mention the bot")
+        }
+        res => panic!("unexpected result: {res:?}"),
+    }
+    assert!(matches!(
+        RequiredCommands::parse("/nope", "MyBot"),
+        Err(ParseError::UnknownCommand(c)) if c == "/nope"
+    ));
+}
+
+#[test]
+fn command_separated_by_any_whitespace() {
+    #[derive(BotCommands, Debug, PartialEq)]
+    #[command(rename_rule = "lowercase")]
+    enum DefaultCommands {
+        Note(String),
+        Ban(u64),
+        Help,
+    }
+
+    assert_eq!(
+        DefaultCommands::parse("/note\nshopping list", "").unwrap(),
+        DefaultCommands::Note("shopping list".to_owned())
+    );
+    assert_eq!(
+        DefaultCommands::parse("/note@bot\t\n milk", "bot").unwrap(),
+        DefaultCommands::Note("milk".to_owned())
+    );
+    assert_eq!(
+        DefaultCommands::parse("/ban  123", "").unwrap(),
+        DefaultCommands::Ban(123)
+    );
+    assert_eq!(
+        DefaultCommands::parse("/help\n", "").unwrap(),
+        DefaultCommands::Help
+    );
+}
+
+#[test]
+fn parse_with_split_collapse_separators() {
+    #[derive(BotCommands, Debug, PartialEq)]
+    #[command(
+        rename_rule = "lowercase",
+        parse_with = "split",
+        collapse_separators
+    )]
+    enum DefaultCommands {
+        Start(u8, String),
+        Help,
+    }
+
+    #[derive(BotCommands, Debug, PartialEq)]
+    #[command(rename_rule = "lowercase", parse_with = "split")]
+    enum StrictCommands {
+        Start(u8, String),
+        Help,
+    }
+
+    assert_eq!(
+        DefaultCommands::Start(10, "hello".to_string()),
+        DefaultCommands::parse("/start 10   hello ", "").unwrap()
+    );
+    assert!(StrictCommands::parse("/start 10   hello", "").is_err());
+
+    #[derive(BotCommands, Debug, PartialEq)]
+    #[command(rename_rule = "lowercase")]
+    enum VariantCommands {
+        #[command(parse_with = "split", collapse_separators)]
+        Pair(u8, u8),
+        #[command(parse_with = "split", separator = ",", collapse_separators)]
+        List(u8, u8),
+    }
+
+    assert_eq!(
+        VariantCommands::parse("/pair 1  2", "").unwrap(),
+        VariantCommands::Pair(1, 2)
+    );
+    assert_eq!(
+        VariantCommands::parse("/list 1,,2", "").unwrap(),
+        VariantCommands::List(1, 2)
+    );
+}
+
+#[test]
+fn parse_message_with_entities() {
+    use teloxide::{
+        types::{Message, User},
+        utils::command::ParseError,
+    };
+
+    #[derive(Debug, PartialEq)]
+    enum Target {
+        Username(String),
+        Id(u64),
+    }
+
+    impl std::str::FromStr for Target {
+        type Err = std::convert::Infallible;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            Ok(Self::Username(s.trim_start_matches('@').to_owned()))
+        }
+    }
+
+    impl From<User> for Target {
+        fn from(user: User) -> Self {
+            Self::Id(user.id.0)
+        }
+    }
+
+    #[derive(BotCommands, Debug, PartialEq)]
+    #[command(rename_rule = "lowercase")]
+    enum DefaultCommands {
+        Note(String),
+        Ban {
+            #[command(from_mention)]
+            target: Target,
+            reason: String,
+        },
+    }
+
+    fn message(text: &str, entities: serde_json::Value) -> Message {
+        serde_json::from_value(serde_json::json!({
+            "message_id": 1,
+            "date": 0,
+            "chat": { "id": 1, "type": "private", "first_name": "A" },
+            "from": { "id": 1, "is_bot": false, "first_name": "A" },
+            "text": text,
+            "entities": entities,
+        }))
+        .unwrap()
+    }
+
+    // "🦀" is 2 UTF-16 code units long
+    let msg = message(
+        "/note@bot 🦀 /ban",
+        serde_json::json!([{ "type": "bot_command", "offset": 0, "length": 9 }]),
+    );
+    assert_eq!(
+        DefaultCommands::parse_message(&msg, "bot").unwrap(),
+        Some(DefaultCommands::Note("🦀 /ban".to_owned()))
+    );
+
+    // Not marked as a command by Telegram
+    let msg = message("`/note x`", serde_json::json!([]));
+    assert_eq!(DefaultCommands::parse_message(&msg, "bot").unwrap(), None);
+
+    let msg = message(
+        "/ban 🦀 John spam",
+        serde_json::json!([
+            { "type": "bot_command", "offset": 0, "length": 4 },
+            {
+                "type": "text_mention",
+                "offset": 8,
+                "length": 4,
+                "user": { "id": 42, "is_bot": false, "first_name": "John" },
+            },
+        ]),
+    );
+    assert_eq!(
+        DefaultCommands::parse_message(&msg, "bot").unwrap(),
+        Some(DefaultCommands::Ban {
+            target: Target::Id(42),
+            reason: "🦀 spam".to_owned()
+        })
+    );
+
+    assert_eq!(
+        DefaultCommands::parse("/ban @john spam", "bot").unwrap(),
+        DefaultCommands::Ban {
+            target: Target::Username("john".to_owned()),
+            reason: "spam".to_owned()
+        }
+    );
+    // Not a mention
+    assert!(matches!(
+        DefaultCommands::parse("/ban me@john.org spam", "bot"),
+        Err(ParseError::TooFewArguments { expected: 1, found: 0, .. })
+    ));
+
+    #[derive(BotCommands, Debug, PartialEq)]
+    #[command(rename_rule = "lowercase", parse_with = "split", separator = ",")]
+    enum SplitCommands {
+        Give {
+            #[command(from_mention)]
+            target: Target,
+            amount: u8,
+            item: String,
+        },
+    }
+
+    let give = SplitCommands::Give {
+        target: Target::Username("bob".to_owned()),
+        amount: 5,
+        item: "x".to_owned(),
+    };
+    assert_eq!(SplitCommands::parse("/give @bob,5,x", "bot").unwrap(), give);
+    assert_eq!(SplitCommands::parse("/give 5,@bob,x", "bot").unwrap(), give);
+    assert_eq!(SplitCommands::parse("/give 5,x,@bob", "bot").unwrap(), give);
+
+    let msg = message(
+        "/give 5,John,x",
+        serde_json::json!([
+            { "type": "bot_command", "offset": 0, "length": 5 },
+            {
+                "type": "text_mention",
+                "offset": 8,
+                "length": 4,
+                "user": { "id": 42, "is_bot": false, "first_name": "John" },
+            },
+        ]),
+    );
+    assert_eq!(
+        SplitCommands::parse_message(&msg, "bot").unwrap(),
+        Some(SplitCommands::Give {
+            target: Target::Id(42),
+            amount: 5,
+            item: "x".to_owned()
+        })
+    );
+}
+
+#[test]
+fn parse_all_commands() {
+    use teloxide::utils::command::ParseError;
+
+    #[derive(BotCommands, Debug, PartialEq)]
+    #[command(rename_rule = "lowercase")]
+    enum DefaultCommands {
+        Ban(u64),
+        #[command(parse_with = "split")]
+        Mute(u64, String),
+    }
+
+    #[derive(BotCommands, Debug, PartialEq)]
+    #[command(rename_rule = "lowercase", unprefixed = "report")]
+    enum ReportingCommands {
+        Ban(u64),
+    }
+
+    let text = "/ban 1\n\n/ban@other 2\nnot a command\n/mute 3 10m\n/ban x";
+    let parsed = DefaultCommands::parse_all(text, "bot");
+    assert_eq!(parsed.len(), 4);
+    assert_eq!(parsed[0].as_ref().unwrap(), &DefaultCommands::Ban(1));
+    assert!(matches!(parsed[1], Err(ParseError::WrongBotName(_))));
+    assert_eq!(
+        parsed[2].as_ref().unwrap(),
+        &DefaultCommands::Mute(3, "10m".to_owned())
+    );
+    assert!(matches!(parsed[3], Err(ParseError::IncorrectFormat(_))));
+
+    let parsed = ReportingCommands::parse_all("/ban 1\nnot a command", "bot");
+    assert_eq!(parsed[0].as_ref().unwrap(), &ReportingCommands::Ban(1));
+    assert!(matches!(
+        &parsed[1],
+        Err(ParseError::UnknownCommand(word)) if word == "not"
+    ));
+
+    let msg: teloxide::types::Message =
+        serde_json::from_value(serde_json::json!({
+            "message_id": 1,
+            "date": 0,
+            "chat": { "id": 1, "type": "private", "first_name": "A" },
+            "from": { "id": 1, "is_bot": false, "first_name": "A" },
+            "text": "hi /ban 1 /mute 2 x",
+            "entities": [
+                { "type": "bot_command", "offset": 3, "length": 4 },
+                { "type": "bot_command", "offset": 10, "length": 5 },
+            ],
+        }))
+        .unwrap();
+    let parsed = DefaultCommands::parse_all_message(&msg, "bot");
+    assert_eq!(
+        parsed.into_iter().collect::<Result<Vec<_>, _>>().unwrap(),
+        [DefaultCommands::Ban(1), DefaultCommands::Mute(2, "x".to_owned())]
+    );
+    assert_eq!(ReportingCommands::parse_all_message(&msg, "bot").len(), 3);
+}
+
+#[test]
+fn deep_link_payloads() {
+    #[derive(BotCommands, Debug, PartialEq)]
+    #[command(rename_rule = "lowercase")]
+    enum RawCommands {
+        #[command(deep_link)]
+        Start(Option<String>),
+        Help,
+    }
+
+    #[derive(BotCommands, Debug, PartialEq)]
+    #[command(rename_rule = "lowercase")]
+    enum Base64Commands {
+        #[command(deep_link = "base64")]
+        Start(String),
+    }
+
+    mod invoice {
+        pub fn encode(id: &u32) -> String {
+            format!("inv-{id}")
+        }
+
+        pub fn decode(s: &str) -> Result<u32, std::num::ParseIntError> {
+            s.trim_start_matches("inv-").parse()
+        }
+    }
+
+    #[derive(BotCommands, Debug, PartialEq)]
+    #[command(rename_rule = "lowercase")]
+    enum CustomCommands {
+        #[command(deep_link = invoice)]
+        Start(u32),
+    }
+
+    const REFERRAL: &str = RawCommands::checked_deep_link_payload("ref_42");
+
+    assert_eq!(
+        RawCommands::parse("/start ref_42", "").unwrap(),
+        RawCommands::Start(Some(REFERRAL.to_owned()))
+    );
+    assert_eq!(
+        RawCommands::parse("/start", "").unwrap(),
+        RawCommands::Start(None)
+    );
+    assert_eq!(
+        RawCommands::deep_link("@my_bot", &REFERRAL.to_owned()).as_deref(),
+        Some("https://t.me/my_bot?start=ref_42")
+    );
+    assert_eq!(RawCommands::deep_link("my_bot", &"a b".to_owned()), None);
+    assert_eq!(RawCommands::deep_link("my_bot", &"a".repeat(65)), None);
+    assert!(
+        RawCommands::parse(&format!("/start {}", "a".repeat(65)), "").is_err()
+    );
+    assert!(RawCommands::parse("/start a.b", "").is_err());
+
+    let payload = "order #1: 2×🦀".to_owned();
+    let link = Base64Commands::deep_link("my_bot", &payload).unwrap();
+    let encoded = link.strip_prefix("https://t.me/my_bot?start=").unwrap();
+    assert_eq!(
+        Base64Commands::parse(&format!("/start {encoded}"), "").unwrap(),
+        Base64Commands::Start(payload)
+    );
+    assert!(Base64Commands::parse("/start a+b", "").is_err());
+
+    assert_eq!(
+        CustomCommands::deep_link("my_bot", &55).as_deref(),
+        Some("https://t.me/my_bot?start=inv-55")
+    );
+    assert_eq!(
+        CustomCommands::parse("/start inv-55", "").unwrap(),
+        CustomCommands::Start(55)
+    );
+}
+
+#[test]
+fn name_patterns() {
+    use teloxide::utils::command::ParseError;
+
+    #[derive(BotCommands, Debug, PartialEq)]
+    #[command(rename_rule = "lowercase")]
+    enum DefaultCommands {
+        #[command(description = "shows an item", pattern = "view_{id}")]
+        View { id: u32 },
+        #[command(pattern = "order_{0}_{1}")]
+        Order(u32, String),
+        #[command(description = "help")]
+        Help,
+    }
+
+    assert_eq!(
+        DefaultCommands::parse("/view_1234", "").unwrap(),
+        DefaultCommands::View { id: 1234 }
+    );
+    assert_eq!(
+        DefaultCommands::parse("/order_55_cancel@my_bot", "my_bot").unwrap(),
+        DefaultCommands::Order(55, "cancel".to_owned())
+    );
+    assert_eq!(
+        DefaultCommands::parse("/help", "").unwrap(),
+        DefaultCommands::Help
+    );
+    assert!(matches!(
+        DefaultCommands::parse("/view_", ""),
+        Err(ParseError::UnknownCommand(_))
+    ));
+    assert!(matches!(
+        DefaultCommands::parse("/view_abc", ""),
+        Err(ParseError::IncorrectFormat(_))
+    ));
+    assert!(matches!(
+        DefaultCommands::parse("/view_1 2", ""),
+        Err(ParseError::TooManyArguments { .. })
+    ));
+
+    assert_eq!(
+        DefaultCommands::View { id: 7 }.format_pattern().as_deref(),
+        Some("/view_7")
+    );
+    assert_eq!(
+        DefaultCommands::Order(1, "pay".to_owned()).format_pattern().as_deref(),
+        Some("/order_1_pay")
+    );
+    assert_eq!(DefaultCommands::Help.format_pattern(), None);
+
+    assert_eq!(
+        DefaultCommands::bot_commands(),
+        vec![teloxide::types::BotCommand::new("/help", "help")]
+    );
+    assert!(DefaultCommands::descriptions().to_string().contains("/view_{id}"));
+}
+
+#[test]
+fn parse_with_regex() {
+    use teloxide::utils::command::ParseError;
+
+    #[derive(BotCommands, Debug, PartialEq)]
+    #[command(rename_rule = "lowercase")]
+    enum DefaultCommands {
+        #[command(parse_with = regex(r"(?P<count>\d+)d(?P<sides>\d+)(?:\+(?P<bonus>\d+))?"))]
+        Roll { count: u8, sides: u8, bonus: Option<u8> },
+        #[command(parse_with = regex(r"at (?P<time>\d\d:\d\d) do (?P<what>.+)"))]
+        Remind { time: String, what: String },
+    }
+
+    assert_eq!(
+        DefaultCommands::parse("/roll 3d6+2", "").unwrap(),
+        DefaultCommands::Roll { count: 3, sides: 6, bonus: Some(2) }
+    );
+    assert_eq!(
+        DefaultCommands::parse("/roll 1d20", "").unwrap(),
+        DefaultCommands::Roll { count: 1, sides: 20, bonus: None }
+    );
+    assert_eq!(
+        DefaultCommands::parse("/remind at 18:30 do thing", "").unwrap(),
+        DefaultCommands::Remind {
+            time: "18:30".to_owned(),
+            what: "thing".to_owned()
+        }
+    );
+    assert!(matches!(
+        DefaultCommands::parse("/roll 3d6 extra", ""),
+        Err(ParseError::IncorrectFormat(_))
+    ));
+    assert!(matches!(
+        DefaultCommands::parse("/roll 300d6", ""),
+        Err(ParseError::IncorrectFormat(_))
+    ));
+}
+
+#[test]
+fn case_insensitive_commands() {
+    use teloxide::utils::command::ParseError;
+
+    #[derive(BotCommands, Debug, PartialEq)]
+    #[command(rename_rule = "lowercase", case_insensitive)]
+    enum DefaultCommands {
+        Start,
+        Help,
+    }
+
+    #[derive(BotCommands, Debug, PartialEq)]
+    #[command(case_insensitive = "unicode")]
+    enum UnicodeCommands {
+        #[command(rename = "помощь")]
+        Help,
+    }
+
+    assert_eq!(
+        DefaultCommands::parse("/Help", "").unwrap(),
+        DefaultCommands::Help
+    );
+    assert_eq!(
+        DefaultCommands::parse("/START@My_Bot", "my_bot").unwrap(),
+        DefaultCommands::Start
+    );
+    assert!(matches!(
+        DefaultCommands::parse("/HELPME", ""),
+        Err(ParseError::UnknownCommand(c)) if c == "/HELPME"
+    ));
+
+    assert_eq!(
+        UnicodeCommands::parse("/ПОМОЩЬ", "").unwrap(),
+        UnicodeCommands::Help
+    );
+}
+
+#[test]
+fn multiple_prefixes() {
+    #[derive(BotCommands, Debug, PartialEq)]
+    #[command(rename_rule = "lowercase", prefix = ["/", "!"])]
+    enum DefaultCommands {
+        #[command(description = "help")]
+        Help,
+        #[command(prefix = ["!", "!!"])]
+        Ban(String),
+        #[command(prefix = "/", rename = "!ban")]
+        Double,
+        #[command(pattern = "view_{id}", prefix = [".", "/"])]
+        View { id: u32 },
+    }
+
+    assert_eq!(
+        DefaultCommands::parse("/help", "").unwrap(),
+        DefaultCommands::Help
+    );
+    assert_eq!(
+        DefaultCommands::parse("!help", "").unwrap(),
+        DefaultCommands::Help
+    );
+    assert_eq!(
+        DefaultCommands::parse("!ban bob", "").unwrap(),
+        DefaultCommands::Ban("bob".to_owned())
+    );
+    assert_eq!(
+        DefaultCommands::parse("!!ban bob", "").unwrap(),
+        DefaultCommands::Ban("bob".to_owned())
+    );
+    assert_eq!(
+        DefaultCommands::parse("/!ban", "").unwrap(),
+        DefaultCommands::Double
+    );
+    assert_eq!(
+        DefaultCommands::parse(".view_3", "").unwrap(),
+        DefaultCommands::View { id: 3 }
+    );
+    assert_eq!(
+        DefaultCommands::View { id: 3 }.format_pattern().as_deref(),
+        Some(".view_3")
+    );
+
+    assert_eq!(DefaultCommands::Ban(String::new()).prefix(), "!");
+    assert_eq!(
+        DefaultCommands::bot_commands()[0],
+        teloxide::types::BotCommand::new("/help", "help")
+    );
+    assert_eq!(
+        DefaultCommands::parse_all("!help\n!!ban alice\n.view_1", "")
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap(),
+        vec![
+            DefaultCommands::Help,
+            DefaultCommands::Ban("alice".to_owned()),
+            DefaultCommands::View { id: 1 },
+        ]
+    );
+}
+
+#[test]
+fn parse_with_runtime_options() {
+    use teloxide::utils::command::ParseError;
+
+    #[derive(BotCommands, Debug, PartialEq)]
+    #[command(rename_rule = "lowercase", parse_options = ParseOptions)]
+    enum DefaultCommands {
+        Help,
+        Ban(String),
+        #[command(pattern = "view_{id}")]
+        View {
+            id: u32,
+        },
+    }
+
+    let defaults = ParseOptions::default();
+    assert_eq!(
+        DefaultCommands::parse_with_options("/help", &defaults).unwrap(),
+        DefaultCommands::Help
+    );
+    // There are no bot usernames by default
+    assert!(matches!(
+        DefaultCommands::parse_with_options("/help@bot", &defaults),
+        Err(ParseError::WrongBotName(name)) if name == "bot"
+    ));
+    assert_eq!(
+        DefaultCommands::parse_with_options(
+            "/help@bot",
+            &ParseOptions { bot_usernames: &["bot"], ..defaults }
+        )
+        .unwrap(),
+        DefaultCommands::Help
+    );
+
+    let options = ParseOptions {
+        prefixes: Some(&["!", "?"]),
+        bot_usernames: &["my_bot", "my_other_bot"],
+        case_insensitive: true,
+    };
+    assert_eq!(
+        DefaultCommands::parse_with_options("!HELP", &options).unwrap(),
+        DefaultCommands::Help
+    );
+    assert_eq!(
+        DefaultCommands::parse_with_options("?ban@my_other_bot bob", &options)
+            .unwrap(),
+        DefaultCommands::Ban("bob".to_owned())
+    );
+    assert_eq!(
+        DefaultCommands::parse_with_options("!view_5", &options).unwrap(),
+        DefaultCommands::View { id: 5 }
+    );
+    assert!(matches!(
+        DefaultCommands::parse_with_options("/help", &options),
+        Err(ParseError::UnknownCommand(_))
+    ));
+    assert!(matches!(
+        DefaultCommands::parse_with_options("!help@bot", &options),
+        Err(ParseError::WrongBotName(name)) if name == "bot"
+    ));
+}
+
+#[test]
+fn keyword_triggers() {
+    use teloxide::utils::command::ParseError;
+
+    #[derive(BotCommands, Debug, PartialEq)]
+    #[command(rename_rule = "lowercase")]
+    enum DefaultCommands {
+        #[command(description = "help")]
+        Help,
+        #[command(
+            description = "main menu",
+            trigger = "menu",
+            trigger_match = "whole_message"
+        )]
+        Menu,
+        #[command(trigger = "stats", trigger_case_insensitive)]
+        Stats(String),
+    }
+
+    assert_eq!(
+        DefaultCommands::parse("/help", "").unwrap(),
+        DefaultCommands::Help
+    );
+    assert_eq!(
+        DefaultCommands::parse("menu", "").unwrap(),
+        DefaultCommands::Menu
+    );
+    assert_eq!(
+        DefaultCommands::parse("  menu\n", "").unwrap(),
+        DefaultCommands::Menu
+    );
+    assert!(matches!(
+        DefaultCommands::parse("menu please", ""),
+        Err(ParseError::UnknownCommand(_))
+    ));
+    assert!(DefaultCommands::parse("Menu", "").is_err());
+    assert_eq!(
+        DefaultCommands::parse("Stats weekly", "").unwrap(),
+        DefaultCommands::Stats("weekly".to_owned())
+    );
+    assert!(DefaultCommands::parse("/stats weekly", "").is_err());
+    assert!(DefaultCommands::parse("statistics", "").is_err());
+
+    assert_eq!(DefaultCommands::Menu.prefixed_command(), "menu");
+    assert_eq!(
+        DefaultCommands::bot_commands(),
+        vec![teloxide::types::BotCommand::new("/help", "help")]
+    );
+    assert_eq!(
+        DefaultCommands::parse_all("hello\nmenu\nSTATS daily\n/help", "")
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap(),
+        vec![
+            DefaultCommands::Menu,
+            DefaultCommands::Stats("daily".to_owned()),
+            DefaultCommands::Help
+        ]
+    );
+}
+
+#[test]
+fn abbreviations() {
+    use teloxide::utils::command::ParseError;
+
+    #[derive(BotCommands, Debug, PartialEq)]
+    #[command(rename_rule = "lowercase", abbreviations = 2)]
+    enum DefaultCommands {
+        Statistics,
+        Start,
+        Stop(String),
+        Help,
+        // Shorter than the minimum length, but still matched in full
+        Q,
+    }
+
+    assert_eq!(
+        DefaultCommands::parse("/stati", "").unwrap(),
+        DefaultCommands::Statistics
+    );
+    assert_eq!(
+        DefaultCommands::parse("/star", "").unwrap(),
+        DefaultCommands::Start
+    );
+    assert_eq!(
+        DefaultCommands::parse("/sto now", "").unwrap(),
+        DefaultCommands::Stop("now".to_owned())
+    );
+    assert_eq!(
+        DefaultCommands::parse("/he", "").unwrap(),
+        DefaultCommands::Help
+    );
+    assert!(matches!(
+        DefaultCommands::parse("/h", ""),
+        Err(ParseError::UnknownCommand(_))
+    ));
+    assert!(matches!(
+        DefaultCommands::parse("/helpme", ""),
+        Err(ParseError::UnknownCommand(_))
+    ));
+    match DefaultCommands::parse("/sta", "") {
+        Err(ParseError::Custom(e)) => assert_eq!(
+            e.to_string(),
+            "Ambiguous command /sta, candidates: /statistics, /start"
+        ),
+        res => panic!("unexpected result: {res:?}"),
+    }
+    assert_eq!(DefaultCommands::parse("/q", "").unwrap(), DefaultCommands::Q);
+
+    assert_eq!(
+        DefaultCommands::abbreviation_candidates("/sta"),
+        vec!["/statistics", "/start"]
+    );
+    assert_eq!(DefaultCommands::abbreviation_candidates("/sto"), vec!["/stop"]);
+    assert!(DefaultCommands::abbreviation_candidates("/s").is_empty());
+    assert!(DefaultCommands::abbreviation_candidates("/x").is_empty());
+}
+
+#[test]
+fn unknown_command_suggestions() {
+    #[derive(BotCommands, Debug, PartialEq)]
+    #[command(rename_rule = "lowercase", prefix = ["/", "!"])]
+    enum DefaultCommands {
+        Help,
+        Start,
+        Statistics,
+        #[command(hide)]
+        Secret,
+    }
+
+    assert_eq!(DefaultCommands::suggestions("/hlep"), vec!["/help"]);
+    assert_eq!(DefaultCommands::suggestions("!HELPP"), vec!["!help"]);
+    assert_eq!(DefaultCommands::suggestions("/statistic"), vec!["/statistics"]);
+    assert_eq!(DefaultCommands::suggestions("/stat"), vec!["/start"]);
+    assert_eq!(DefaultCommands::suggestions("/settings"), Vec::<&str>::new());
+    assert_eq!(DefaultCommands::suggestions("/secret"), Vec::<&str>::new());
+    assert_eq!(DefaultCommands::suggestions("/sart"), vec!["/start"]);
+}
+
+#[test]
+fn text_only_commands() {
+    #[derive(BotCommands, Debug, PartialEq)]
+    #[command(rename_rule = "kebab-case", check_menu)]
+    enum DefaultCommands {
+        #[command(description = "show help")]
+        Help,
+        #[command(text_only)]
+        ShowStats,
+        // Not validated, since there is no description
+        Start,
+    }
+
+    assert_eq!(
+        DefaultCommands::parse("/show-stats", "").unwrap(),
+        DefaultCommands::ShowStats
+    );
+    assert_eq!(
+        DefaultCommands::bot_commands(),
+        vec![
+            teloxide::types::BotCommand::new("/help", "show help"),
+            teloxide::types::BotCommand::new("/start", ""),
+        ]
+    );
+}
+
+#[test]
+fn same_name_with_different_prefixes() {
+    #[derive(BotCommands, Debug, PartialEq)]
+    #[command(rename_rule = "lowercase")]
+    enum DefaultCommands {
+        Help,
+        #[command(prefix = "!", rename = "help")]
+        AdminHelp,
+        #[command(trigger = "help")]
+        KeywordHelp,
+    }
+
+    assert_eq!(
+        DefaultCommands::parse("/help", "").unwrap(),
+        DefaultCommands::Help
+    );
+    assert_eq!(
+        DefaultCommands::parse("!help", "").unwrap(),
+        DefaultCommands::AdminHelp
+    );
+    assert_eq!(
+        DefaultCommands::parse("help", "").unwrap(),
+        DefaultCommands::KeywordHelp
+    );
+}
+
+#[test]
+fn visibility_controls() {
+    use teloxide::types::BotCommand;
+
+    #[derive(BotCommands, Debug, PartialEq)]
+    #[command(rename_rule = "lowercase")]
+    enum DefaultCommands {
+        #[command(description = "off", hide_from_help)]
+        Toggle,
+        #[command(description = "show help", hide_from_menu)]
+        Help,
+        #[command(hide)]
+        Secret,
+    }
+
+    assert_eq!(
+        DefaultCommands::descriptions().to_string(),
+        "/help — show help"
+    );
+    assert_eq!(
+        DefaultCommands::bot_commands(),
+        vec![BotCommand::new("/toggle", "off")]
+    );
+    assert_eq!(
+        DefaultCommands::parse("/secret", "").unwrap(),
+        DefaultCommands::Secret
+    );
+}
+
+#[test]
+fn skip_variants() {
+    use teloxide::utils::command::ParseError;
+
+    // Doesn't implement `FromStr`
+    #[derive(Debug, PartialEq)]
+    struct TaskId(u64);
+
+    #[derive(BotCommands, Debug, PartialEq)]
+    #[command(rename_rule = "lowercase")]
+    enum DefaultCommands {
+        #[command(description = "show help")]
+        Help,
+        #[command(skip)]
+        Internal(TaskId),
+    }
+
+    assert_eq!(
+        DefaultCommands::parse("/help", "").unwrap(),
+        DefaultCommands::Help
+    );
+    assert!(matches!(
+        DefaultCommands::parse("/internal 1", ""),
+        Err(ParseError::UnknownCommand(c)) if c == "/internal"
+    ));
+    assert_eq!(
+        DefaultCommands::descriptions().to_string(),
+        "/help — show help"
+    );
+    assert_eq!(DefaultCommands::bot_commands().len(), 1);
+    assert_eq!(DefaultCommands::suggestions("/internl"), Vec::<&str>::new());
+    assert_eq!(DefaultCommands::Internal(TaskId(1)).description(), None);
+}
+
+#[test]
+fn deprecated_commands() {
+    #[derive(BotCommands, Debug, PartialEq)]
+    #[command(rename_rule = "lowercase")]
+    enum DefaultCommands {
+        #[command(description = "show statistics")]
+        Stats,
+        #[command(deprecated = "use /stats")]
+        Statistics,
+    }
+
+    assert_eq!(
+        DefaultCommands::parse_detailed("/statistics", "").unwrap(),
+        (DefaultCommands::Statistics, Some("use /stats"))
+    );
+    assert_eq!(
+        DefaultCommands::parse_detailed("/stats", "").unwrap(),
+        (DefaultCommands::Stats, None)
+    );
+    assert_eq!(
+        DefaultCommands::descriptions().to_string(),
+        "/stats — show statistics"
+    );
+    assert_eq!(DefaultCommands::bot_commands().len(), 1);
+
+    #[derive(BotCommands, Debug, PartialEq)]
+    #[command(rename_rule = "lowercase")]
+    enum CurrentCommands {
+        Stats,
+    }
+
+    assert_eq!(
+        CurrentCommands::parse_detailed("/stats", "").unwrap(),
+        (CurrentCommands::Stats, None)
+    );
+}
+
+#[test]
+fn usage_and_arguments() {
+    #[derive(BotCommands, Debug, PartialEq)]
+    #[command(rename_rule = "lowercase", arguments = CommandArgument)]
+    enum DefaultCommands {
+        #[command(
+            description = "ban a user",
+            parse_with = regex(r"(?P<user_id>\d+)(\s+(?P<reason>.+))?")
+        )]
+        Ban {
+            user_id: u64,
+            reason: Option<String>,
+        },
+        #[command(description = "repeat the text")]
+        Echo(#[command(rename = "text")] String),
+        #[command(parse_with = "split")]
+        Pair(u8, u8),
+        Help,
+    }
+
+    assert_eq!(
+        DefaultCommands::parse("/ban 10 spam", "").unwrap(),
+        DefaultCommands::Ban { user_id: 10, reason: Some("spam".to_owned()) }
+    );
+
+    let ban = DefaultCommands::Ban { user_id: 10, reason: None };
+    assert_eq!(ban.usage(), "/ban <user_id> [reason]");
+    assert_eq!(
+        ban.arguments(),
+        [
+            CommandArgument {
+                name: "user_id",
+                type_name: "u64",
+                optional: false,
+                variadic: false,
+            },
+            CommandArgument {
+                name: "reason",
+                type_name: "Option<String>",
+                optional: true,
+                variadic: false,
+            },
+        ]
+    );
+    assert_eq!(ban.argument_separator(), None);
+
+    let echo = DefaultCommands::Echo(String::new());
+    assert_eq!(echo.usage(), "/echo <text...>");
+    assert!(echo.arguments()[0].variadic);
+
+    let pair = DefaultCommands::Pair(1, 2);
+    assert_eq!(pair.usage(), "/pair <arg0> <arg1>");
+    assert_eq!(pair.argument_separator(), Some(" "));
+    assert_eq!(DefaultCommands::Help.usage(), "/help");
+
+    assert_eq!(
+        DefaultCommands::descriptions().to_string(),
+        "/ban <user_id> [reason] — ban a user\n/echo <text...> — repeat the \
+         text\n/pair\n/help"
+    );
+}
+
+#[test]
+fn help_categories_and_order() {
+    use teloxide::types::BotCommand;
+
+    #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+    enum Perm {
+        User,
+        Admin,
+    }
+
+    #[derive(BotCommands, Debug, PartialEq)]
+    #[command(rename_rule = "lowercase", permission_type = Perm)]
+    enum DefaultCommands {
+        #[command(description = "ban a user", category = "Moderation")]
+        Ban,
+        #[command(description = "show help", order = 0)]
+        Help,
+        #[command(
+            description = "warn a user",
+            category = "Moderation",
+            order = 1,
+            permission = Perm::Admin
+        )]
+        Warn,
+        #[command(description = "show stats", category = "Stats")]
+        Stats,
+        #[command(description = "start the bot")]
+        Start,
+    }
+
+    assert_eq!(
+        DefaultCommands::descriptions().to_string(),
+        "/help — show help\n/start — start the bot\n\nModeration\n/warn — \
+         warn a user\n/ban — ban a user\n\nStats\n/stats — show stats"
+    );
+    assert_eq!(
+        DefaultCommands::descriptions_for(Perm::User).to_string(),
+        "/help — show help\n/start — start the bot\n\nModeration\n/ban — ban \
+         a user\n\nStats\n/stats — show stats"
+    );
+    assert_eq!(
+        DefaultCommands::descriptions_for(Perm::Admin).to_string(),
+        DefaultCommands::descriptions().to_string()
+    );
+    assert_eq!(
+        DefaultCommands::bot_commands()
+            .into_iter()
+            .map(|c| c.command)
+            .collect::<Vec<_>>(),
+        ["/help", "/start", "/warn", "/ban", "/stats"]
+    );
+    assert_eq!(
+        DefaultCommands::bot_commands_for(Perm::User)[2],
+        BotCommand::new("/ban", "ban a user")
+    );
+}